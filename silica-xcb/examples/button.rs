@@ -28,6 +28,7 @@ fn main() -> silica_xcb::Result<()> {
             times_clicked.increment();
         }
     });
+    button.set_tooltip(Some("Increments the counter".to_string()));
     root.add_child(button);
 
     let label = Label::new(root.gui());
@@ -0,0 +1,124 @@
+use silica::signal::{Key, KeyPress, KeyRelease, Modifiers};
+use xcb::{x, xkb as xcb_xkb};
+use xkbcommon::xkb::{self, keysyms};
+
+fn map_keysym(keysym: xkb::Keysym) -> Key {
+    match keysym.raw() {
+        keysyms::KEY_space => Key::Space,
+        keysyms::KEY_Return | keysyms::KEY_KP_Enter => Key::Enter,
+        keysyms::KEY_Tab | keysyms::KEY_ISO_Left_Tab => Key::Tab,
+        keysyms::KEY_BackSpace => Key::Backspace,
+        keysyms::KEY_Escape => Key::Escape,
+        keysyms::KEY_Delete | keysyms::KEY_KP_Delete => Key::Delete,
+        keysyms::KEY_Insert | keysyms::KEY_KP_Insert => Key::Insert,
+        keysyms::KEY_Home | keysyms::KEY_KP_Home => Key::Home,
+        keysyms::KEY_End | keysyms::KEY_KP_End => Key::End,
+        keysyms::KEY_Page_Up | keysyms::KEY_KP_Page_Up => Key::PageUp,
+        keysyms::KEY_Page_Down | keysyms::KEY_KP_Page_Down => Key::PageDown,
+        keysyms::KEY_Left | keysyms::KEY_KP_Left => Key::Left,
+        keysyms::KEY_Right | keysyms::KEY_KP_Right => Key::Right,
+        keysyms::KEY_Up | keysyms::KEY_KP_Up => Key::Up,
+        keysyms::KEY_Down | keysyms::KEY_KP_Down => Key::Down,
+        keysyms::KEY_Shift_L | keysyms::KEY_Shift_R => Key::Shift,
+        keysyms::KEY_Control_L | keysyms::KEY_Control_R => Key::Control,
+        keysyms::KEY_Alt_L | keysyms::KEY_Alt_R => Key::Alt,
+        keysyms::KEY_Super_L | keysyms::KEY_Super_R => Key::Logo,
+        keysyms::KEY_Caps_Lock => Key::CapsLock,
+        raw @ keysyms::KEY_F1..=keysyms::KEY_F24 => Key::F((raw - keysyms::KEY_F1 + 1) as u8),
+        _ => keysym
+            .key_char()
+            .filter(|ch| !ch.is_control())
+            .map(Key::Character)
+            .unwrap_or(Key::Unidentified),
+    }
+}
+
+/// Translates X keycodes through the server's XKB keymap.
+pub(crate) struct Keyboard {
+    state: xkb::State,
+}
+
+impl Keyboard {
+    pub(crate) fn new(conn: &xcb::Connection) -> Option<Self> {
+        let mut major = 0;
+        let mut minor = 0;
+        let mut base_event = 0;
+        let mut base_error = 0;
+        if !xkb::x11::setup_xkb_extension(
+            conn,
+            xkb::x11::MIN_MAJOR_XKB_VERSION,
+            xkb::x11::MIN_MINOR_XKB_VERSION,
+            xkb::x11::SetupXkbExtensionFlags::NoFlags,
+            &mut major,
+            &mut minor,
+            &mut base_event,
+            &mut base_error,
+        ) {
+            return None;
+        }
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let device_id = xkb::x11::get_core_keyboard_device_id(conn);
+        let keymap = xkb::x11::keymap_new_from_device(
+            &context,
+            conn,
+            device_id,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        );
+        let state = xkb::x11::state_new_from_device(&keymap, conn, device_id);
+        // The server keeps the keyboard state, including keys pressed while another window had
+        // the focus. Follow it instead of replaying our own key events.
+        conn.send_and_check_request(&xcb_xkb::SelectEvents {
+            device_spec: device_id as xcb_xkb::DeviceSpec,
+            affect_which: xcb_xkb::EventType::STATE_NOTIFY,
+            clear: xcb_xkb::EventType::empty(),
+            select_all: xcb_xkb::EventType::STATE_NOTIFY,
+            affect_map: xcb_xkb::MapPart::empty(),
+            map: xcb_xkb::MapPart::empty(),
+            details: &[],
+        })
+        .ok()?;
+        Some(Keyboard { state })
+    }
+
+    /// Applies an XKB `StateNotify` event from the server.
+    pub(crate) fn update_state(&mut self, ev: &xcb_xkb::StateNotifyEvent) {
+        self.state.update_mask(
+            ev.base_mods().bits(),
+            ev.latched_mods().bits(),
+            ev.locked_mods().bits(),
+            ev.base_group() as u32,
+            ev.latched_group() as u32,
+            ev.locked_group() as u32,
+        );
+    }
+
+    // X reports the modifier state from just before the event, so pressing Shift does not report
+    // itself as shifted.
+    fn modifiers(state: x::KeyButMask) -> Modifiers {
+        Modifiers {
+            shift: state.contains(x::KeyButMask::SHIFT),
+            ctrl: state.contains(x::KeyButMask::CONTROL),
+            alt: state.contains(x::KeyButMask::MOD1),
+            logo: state.contains(x::KeyButMask::MOD4),
+        }
+    }
+
+    pub(crate) fn key_press(&self, ev: &x::KeyPressEvent, repeat: bool) -> KeyPress {
+        let keycode = xkb::Keycode::from(ev.detail());
+        let text = Some(self.state.key_get_utf8(keycode))
+            .filter(|text| !text.is_empty() && !text.chars().any(char::is_control));
+        KeyPress {
+            key: map_keysym(self.state.key_get_one_sym(keycode)),
+            modifiers: Self::modifiers(ev.state()),
+            repeat,
+            text,
+        }
+    }
+    pub(crate) fn key_release(&self, ev: &x::KeyReleaseEvent) -> KeyRelease {
+        let keycode = xkb::Keycode::from(ev.detail());
+        KeyRelease {
+            key: map_keysym(self.state.key_get_one_sym(keycode)),
+            modifiers: Self::modifiers(ev.state()),
+        }
+    }
+}
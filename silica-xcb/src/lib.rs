@@ -1,12 +1,26 @@
-use std::{cell::Cell, rc::Rc};
+mod keyboard;
+mod wake;
 
+use std::{
+    cell::{Cell, RefCell},
+    os::fd::AsRawFd,
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
+
+use keyboard::Keyboard;
 use pangocairo::pango;
 use silica::{
+    border_dashes, border_quads, border_wedges,
+    signal::PointerButton,
     taffy::{geometry::Point, prelude::*},
     widget::Container,
-    GraphicsContext, HorizontalAlign, TextSection, ThemeColor, VerticalAlign,
+    BorderStyle, Color, CornerRadius, DamageRect, Gradient, GradientShape, GraphicsContext,
+    HorizontalAlign, Shadow, StyleColor, TextSection, Theme, ThemeColor, VerticalAlign,
 };
-use xcb::{x, Xid};
+use wake::Wake;
+use xcb::{x, xkb, Xid};
 
 pub use xcb::Result;
 
@@ -18,7 +32,63 @@ xcb::atoms_struct! {
     }
 }
 
-pub struct CairoContext(cairo::Context, pango::Context);
+pub struct CairoContext(cairo::Context, pango::Context, Rc<Theme>);
+
+impl CairoContext {
+    fn rounded_rect_path(&self, point: Point<f32>, size: Size<f32>, radius: CornerRadius) {
+        use std::f64::consts::{FRAC_PI_2, PI};
+        let radius = radius.clamp(size);
+        let (x, y) = (point.x as f64, point.y as f64);
+        let (width, height) = (size.width as f64, size.height as f64);
+        let [top_left, top_right, bottom_right, bottom_left] = [
+            radius.top_left,
+            radius.top_right,
+            radius.bottom_right,
+            radius.bottom_left,
+        ]
+        .map(f64::from);
+        self.0.new_sub_path();
+        self.0.arc(
+            x + width - top_right,
+            y + top_right,
+            top_right,
+            -FRAC_PI_2,
+            0.0,
+        );
+        self.0.arc(
+            x + width - bottom_right,
+            y + height - bottom_right,
+            bottom_right,
+            0.0,
+            FRAC_PI_2,
+        );
+        self.0.arc(
+            x + bottom_left,
+            y + height - bottom_left,
+            bottom_left,
+            FRAC_PI_2,
+            PI,
+        );
+        self.0
+            .arc(x + top_left, y + top_left, top_left, PI, PI + FRAC_PI_2);
+        self.0.close_path();
+    }
+    fn polygon_path(&self, points: &[Point<f32>]) {
+        self.0.new_sub_path();
+        for point in points {
+            self.0.line_to(point.x as f64, point.y as f64);
+        }
+        self.0.close_path();
+    }
+    fn set_source_color(&self, color: Color) {
+        self.0.set_source_rgba(
+            color.r as f64,
+            color.g as f64,
+            color.b as f64,
+            color.a as f64,
+        );
+    }
+}
 
 impl silica::GraphicsContext for CairoContext {
     fn save(&mut self) {
@@ -30,47 +100,100 @@ impl silica::GraphicsContext for CairoContext {
     fn translate(&mut self, tx: f32, ty: f32) {
         self.0.translate(tx as f64, ty as f64);
     }
-
-    fn set_color(&mut self, color: ThemeColor) {
-        let rgba = color.to_rgba();
-        self.0.set_source_rgba(
-            rgba[0] as f64,
-            rgba[1] as f64,
-            rgba[2] as f64,
-            rgba[3] as f64,
+    fn clip_rect(&mut self, point: Point<f32>, size: Size<f32>) {
+        self.0.rectangle(
+            point.x as f64,
+            point.y as f64,
+            size.width as f64,
+            size.height as f64,
         );
+        self.0.clip();
     }
-    fn draw_border(&mut self, size: Size<f32>, border: Rect<LengthPercentage>) {
-        let size = size.map(|v| v as f64);
-        let border = border.map(|val| match val {
-            LengthPercentage::Points(points) => points as f64,
-            LengthPercentage::Percent(_) => 0.0,
-        });
-        self.0.move_to(0.5, 0.5);
-        if border.top > 0.0 {
-            self.0.set_line_width(border.top);
-            self.0.line_to(size.width - 0.5, 0.5);
-        } else {
-            self.0.move_to(size.width - 0.5, 0.5);
-        }
-        if border.right > 0.0 {
-            self.0.set_line_width(border.right);
-            self.0.line_to(size.width - 0.5, size.height - 0.5);
-        } else {
-            self.0.move_to(size.width - 0.5, size.height - 0.5);
-        }
-        if border.bottom > 0.0 {
-            self.0.set_line_width(border.bottom);
-            self.0.line_to(0.5, size.height - 0.5);
-        } else {
-            self.0.move_to(0.5, size.height - 0.5);
+    fn clip_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: f32) {
+        self.rounded_rect_path(point, size, CornerRadius::all(radius));
+        self.0.clip();
+    }
+
+    fn set_color(&mut self, color: StyleColor) {
+        self.set_source_color(color.resolve(&self.2));
+    }
+    fn draw_border_sides(
+        &mut self,
+        size: Size<f32>,
+        border: Rect<f32>,
+        colors: Rect<Option<StyleColor>>,
+        radius: CornerRadius,
+        style: BorderStyle,
+    ) {
+        let radius = radius.clamp(size);
+        if radius.is_zero() && style == BorderStyle::Solid {
+            let quads = border_quads(size, border);
+            let sides = [
+                (border.top, colors.top, quads.top),
+                (border.right, colors.right, quads.right),
+                (border.bottom, colors.bottom, quads.bottom),
+                (border.left, colors.left, quads.left),
+            ];
+            for (side_width, color, quad) in sides {
+                if let Some(color) = color.filter(|_| side_width > 0.0) {
+                    self.set_color(color);
+                    self.fill_polygons(&[&quad]);
+                }
+            }
+            return;
         }
-        if border.left > 0.0 {
-            self.0.set_line_width(border.left);
-            self.0.line_to(0.5, 0.5);
+        let (width, height) = (size.width, size.height);
+        let inner_size = Size {
+            width: width - border.left - border.right,
+            height: height - border.top - border.bottom,
+        };
+        let inner_radius = CornerRadius {
+            top_left: radius.top_left - border.top.max(border.left),
+            top_right: radius.top_right - border.top.max(border.right),
+            bottom_right: radius.bottom_right - border.bottom.max(border.right),
+            bottom_left: radius.bottom_left - border.bottom.max(border.left),
+        };
+        let wedges = border_wedges(size, border);
+        // Each side and whether it runs horizontally, for dashes.
+        let sides = [
+            (border.top, colors.top, wedges.top, true),
+            (border.right, colors.right, wedges.right, false),
+            (border.bottom, colors.bottom, wedges.bottom, true),
+            (border.left, colors.left, wedges.left, false),
+        ];
+        for (side_width, color, wedge, horizontal) in sides {
+            let Some(color) = color.filter(|_| side_width > 0.0) else {
+                continue;
+            };
+            self.0.save().unwrap();
+            self.polygon_path(&wedge);
+            self.0.clip();
+            // Dashes are bands across the box, the same as the headless backend draws.
+            if style != BorderStyle::Solid {
+                let length = if horizontal { width } else { height };
+                for (start, end) in border_dashes(length, side_width, style) {
+                    let (start, end) = (start as f64, end as f64);
+                    if horizontal {
+                        self.0.rectangle(start, 0.0, end - start, height as f64);
+                    } else {
+                        self.0.rectangle(0.0, start, width as f64, end - start);
+                    }
+                }
+                self.0.clip();
+            }
+            self.set_color(color);
+            self.0.set_fill_rule(cairo::FillRule::EvenOdd);
+            self.rounded_rect_path(Point::ZERO, size, radius);
+            if inner_size.width > 0.0 && inner_size.height > 0.0 {
+                let inner = Point {
+                    x: border.left,
+                    y: border.top,
+                };
+                self.rounded_rect_path(inner, inner_size, inner_radius);
+            }
+            self.0.fill().unwrap();
+            self.0.restore().unwrap();
         }
-        self.0.set_line_cap(cairo::LineCap::Square);
-        self.0.stroke().unwrap();
     }
     fn draw_rect(&mut self, point: Point<f32>, size: Size<f32>) {
         self.0.rectangle(
@@ -81,6 +204,111 @@ impl silica::GraphicsContext for CairoContext {
         );
         self.0.fill().unwrap();
     }
+    fn fill_polygons(&mut self, polygons: &[&[Point<f32>]]) {
+        for points in polygons {
+            self.polygon_path(points);
+        }
+        self.0.fill().unwrap();
+    }
+    fn draw_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: CornerRadius) {
+        self.rounded_rect_path(point, size, radius);
+        self.0.fill().unwrap();
+    }
+    fn draw_gradient(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        gradient: &Gradient,
+    ) {
+        let add_stops = |pattern: &cairo::Gradient| {
+            for stop in &gradient.stops {
+                let color = stop.color.resolve(&self.2);
+                pattern.add_color_stop_rgba(
+                    stop.offset as f64,
+                    color.r as f64,
+                    color.g as f64,
+                    color.b as f64,
+                    color.a as f64,
+                );
+            }
+        };
+        let (width, height) = (size.width as f64, size.height as f64);
+        let center_x = point.x as f64 + width / 2.0;
+        let center_y = point.y as f64 + height / 2.0;
+        self.0.save().unwrap();
+        match gradient.shape {
+            GradientShape::Linear { angle } => {
+                let (sin, cos) = (angle as f64).to_radians().sin_cos();
+                let half = (width * sin.abs() + height * cos.abs()) / 2.0;
+                let pattern = cairo::LinearGradient::new(
+                    center_x - sin * half,
+                    center_y + cos * half,
+                    center_x + sin * half,
+                    center_y - cos * half,
+                );
+                add_stops(&pattern);
+                self.0.set_source(&pattern).unwrap();
+            }
+            GradientShape::Radial => {
+                let reach = (width / 2.0).hypot(height / 2.0);
+                let pattern =
+                    cairo::RadialGradient::new(center_x, center_y, 0.0, center_x, center_y, reach);
+                add_stops(&pattern);
+                self.0.set_source(&pattern).unwrap();
+            }
+        }
+        self.rounded_rect_path(point, size, radius);
+        self.0.fill().unwrap();
+        self.0.restore().unwrap();
+    }
+    fn draw_shadow(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        shadow: &Shadow,
+    ) {
+        let color = shadow.color.resolve(&self.2);
+        let outset = shadow.outset();
+        self.0.save().unwrap();
+        // Only paint outside the widget, so that a translucent background doesn't show it.
+        self.0.set_fill_rule(cairo::FillRule::EvenOdd);
+        self.0.rectangle(
+            (point.x - outset.left) as f64,
+            (point.y - outset.top) as f64,
+            (size.width + outset.left + outset.right) as f64,
+            (size.height + outset.top + outset.bottom) as f64,
+        );
+        self.rounded_rect_path(point, size, radius);
+        self.0.clip();
+        // Cairo can't blur, so stack outlines that grow across the blur width. Each gets the
+        // alpha that makes them all add up to the shadow color where they overlap.
+        let steps = (shadow.blur.ceil() as usize).clamp(1, 16);
+        let alpha = 1.0 - (1.0 - color.a).powf(1.0 / steps as f32);
+        self.set_source_color(color.with_alpha(alpha));
+        for step in 0..steps {
+            let fraction = (step as f32 + 0.5) / steps as f32 - 0.5;
+            let grow = shadow.spread + shadow.blur * fraction;
+            let shadow_size = size.map(|v| v + 2.0 * grow);
+            if shadow_size.width <= 0.0 || shadow_size.height <= 0.0 {
+                continue;
+            }
+            let origin = Point {
+                x: point.x + shadow.offset.x - grow,
+                y: point.y + shadow.offset.y - grow,
+            };
+            let grown = CornerRadius {
+                top_left: radius.top_left + grow,
+                top_right: radius.top_right + grow,
+                bottom_right: radius.bottom_right + grow,
+                bottom_left: radius.bottom_left + grow,
+            };
+            self.rounded_rect_path(origin, shadow_size, grown);
+            self.0.fill().unwrap();
+        }
+        self.0.restore().unwrap();
+    }
     fn draw_text(&mut self, point: Point<f32>, size: Size<f32>, text: &TextSection) {
         pangocairo::update_context(&self.0, &self.1);
         let layout = pango::Layout::new(&self.1);
@@ -125,6 +353,8 @@ pub struct Window {
     window: x::Window,
     surface: cairo::XCBSurface,
     size: Cell<Size<u16>>,
+    keyboard: RefCell<Keyboard>,
+    wake: Arc<Wake>,
     gui: Rc<silica::Gui>,
     root: Container,
 }
@@ -163,7 +393,8 @@ impl Window {
     }
 
     pub fn new(root: Container) -> Rc<Self> {
-        let (conn, screen_num) = xcb::Connection::connect(None).unwrap();
+        let (conn, screen_num) =
+            xcb::Connection::connect_with_extensions(None, &[xcb::Extension::Xkb], &[]).unwrap();
         let atoms = Atoms::intern_all(&conn).unwrap();
         let screen = conn.get_setup().roots().nth(screen_num as usize).unwrap();
         let window: x::Window = conn.generate_id();
@@ -189,7 +420,8 @@ impl Window {
                     | x::EventMask::POINTER_MOTION
                     | x::EventMask::BUTTON_PRESS
                     | x::EventMask::BUTTON_RELEASE
-                    | x::EventMask::KEY_PRESS,
+                    | x::EventMask::KEY_PRESS
+                    | x::EventMask::KEY_RELEASE,
             )],
         });
 
@@ -205,6 +437,12 @@ impl Window {
         });
 
         let surface = Self::make_surface(&conn, screen, window, size);
+        let keyboard = Keyboard::new(&conn).expect("XKB extension not available");
+        let wake = Arc::new(Wake::new().expect("failed to create eventfd"));
+        gui.set_proxy_waker({
+            let wake = wake.clone();
+            move || wake.wake()
+        });
 
         Rc::new(Window {
             xcb: conn,
@@ -212,6 +450,8 @@ impl Window {
             window,
             surface,
             size: Cell::new(size),
+            keyboard: RefCell::new(keyboard),
+            wake,
             gui,
             root,
         })
@@ -243,20 +483,77 @@ impl Window {
         });
     }
 
-    fn queue_redraw(&self) {
-        self.xcb.send_request(&x::ClearArea {
-            exposures: true,
-            window: self.window,
-            x: 0,
-            y: 0,
-            width: 1,
-            height: 1,
-        });
+    // Repaints the damaged parts of the window, clipping cairo to them.
+    fn redraw(&self) {
+        let size = self.size.get().map(|v| v as f32);
+        self.gui.emit_layout(Some(size));
+        let damage = self.gui.take_damage();
+        if damage.is_empty() {
+            return;
+        }
+
+        let cairo_context =
+            cairo::Context::new(&self.surface).expect("failed to create cairo context");
+        for rect in damage.rects() {
+            cairo_context.rectangle(
+                rect.origin.x.into(),
+                rect.origin.y.into(),
+                rect.size.width.into(),
+                rect.size.height.into(),
+            );
+        }
+        cairo_context.clip();
+        let pango_context = pangocairo::create_context(&cairo_context);
+        let mut context = CairoContext(cairo_context, pango_context, self.gui.theme());
+        context.set_color(ThemeColor::Background.into());
+        context.0.paint().unwrap();
+
+        self.gui
+            .draw_region(&mut context, self.root.clone(), &damage);
+
+        self.surface.flush();
+    }
+    // Waits for the next event. Returns `None` once `deadline` has passed or when woken through
+    // a `GuiProxy`.
+    fn wait_for_event_until(&self, deadline: Option<Instant>) -> Result<Option<xcb::Event>> {
+        loop {
+            if let Some(event) = self.xcb.poll_for_event()? {
+                return Ok(Some(event));
+            }
+            let timeout_ms = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if timeout.is_zero() {
+                        return Ok(None);
+                    }
+                    // Round up so the timer is due when poll returns.
+                    timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32
+                }
+                None => -1,
+            };
+            let mut fds = [self.xcb.as_raw_fd(), self.wake.as_raw_fd()].map(|fd| libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            });
+            unsafe {
+                libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms);
+            }
+            if fds[1].revents & libc::POLLIN != 0 {
+                self.wake.clear();
+                return Ok(None);
+            }
+        }
     }
     pub fn run_event_loop(self: Rc<Self>) -> Result<()> {
         self.xcb.flush()?;
+        let mut queued_event = None;
         loop {
-            let event = match self.xcb.wait_for_event() {
+            let event = match queued_event.take() {
+                Some(event) => Ok(Some(event)),
+                None => self.wait_for_event_until(self.gui.next_deadline()),
+            };
+            let event = match event {
                 Err(xcb::Error::Connection(err)) => {
                     panic!("unexpected I/O error: {}", err);
                 }
@@ -266,21 +563,34 @@ impl Window {
                 Ok(event) => event,
             };
 
+            self.gui.emit_timers();
+            self.gui.emit_proxy_events();
+
             // println!("Received event {:#?}", event);
+            let Some(event) = event else {
+                // Woken by a timer or a `GuiProxy`, with no event to handle.
+                if self.gui.has_damage() {
+                    self.redraw();
+                }
+                self.xcb.flush()?;
+                continue;
+            };
             match event {
-                xcb::Event::X(x::Event::Expose(_)) => {
-                    let cairo_context =
-                        cairo::Context::new(&self.surface).expect("failed to create cairo context");
-                    let pango_context = pangocairo::create_context(&cairo_context);
-                    let mut context = CairoContext(cairo_context, pango_context);
-                    context.set_color(ThemeColor::Background);
-                    context.0.paint().unwrap();
-
-                    let size = self.size.get().map(|v| v as f32);
-                    self.gui.emit_layout(Some(size));
-                    self.gui.draw(&mut context, self.root.clone());
-
-                    self.surface.flush();
+                xcb::Event::X(x::Event::Expose(ev)) => {
+                    self.gui.damage(DamageRect::new(
+                        Point {
+                            x: ev.x().into(),
+                            y: ev.y().into(),
+                        },
+                        Size {
+                            width: ev.width().into(),
+                            height: ev.height().into(),
+                        },
+                    ));
+                    // Wait for the rest of the exposed areas before repainting.
+                    if ev.count() > 0 {
+                        continue;
+                    }
                 }
                 xcb::Event::X(x::Event::ConfigureNotify(ev)) => {
                     let size = Size {
@@ -292,28 +602,55 @@ impl Window {
                         self.surface
                             .set_size(size.width as i32, size.height as i32)
                             .expect("failed to resize surface");
+                        self.gui.damage_all();
                     }
                 }
                 xcb::Event::X(x::Event::MotionNotify(ev)) => {
                     self.gui
                         .emit_pointer_motion(ev.event_x().into(), ev.event_y().into());
                 }
-                xcb::Event::X(x::Event::ButtonPress(ev)) => {
-                    if ev.detail() == 1 {
-                        // LMB
-                        self.gui
-                            .emit_pointer_button(silica::signal::PointerButton::Primary(true));
-                    }
+                xcb::Event::X(x::Event::ButtonPress(ev)) => match ev.detail() {
+                    1 => self.gui.emit_pointer_button(PointerButton::Primary(true)),
+                    2 => self.gui.emit_pointer_button(PointerButton::Middle(true)),
+                    3 => self.gui.emit_pointer_button(PointerButton::Secondary(true)),
+                    4 => self.gui.emit_scroll(0.0, -1.0),
+                    5 => self.gui.emit_scroll(0.0, 1.0),
+                    6 => self.gui.emit_scroll(-1.0, 0.0),
+                    7 => self.gui.emit_scroll(1.0, 0.0),
+                    _ => {}
+                },
+                xcb::Event::X(x::Event::ButtonRelease(ev)) => match ev.detail() {
+                    1 => self.gui.emit_pointer_button(PointerButton::Primary(false)),
+                    2 => self.gui.emit_pointer_button(PointerButton::Middle(false)),
+                    3 => self
+                        .gui
+                        .emit_pointer_button(PointerButton::Secondary(false)),
+                    _ => {}
+                },
+                xcb::Event::X(x::Event::KeyPress(ev)) => {
+                    let key = self.keyboard.borrow().key_press(&ev, false);
+                    self.gui.emit_key(key);
                 }
-                xcb::Event::X(x::Event::ButtonRelease(ev)) => {
-                    if ev.detail() == 1 {
-                        // LMB
-                        self.gui
-                            .emit_pointer_button(silica::signal::PointerButton::Primary(false));
+                xcb::Event::X(x::Event::KeyRelease(ev)) => {
+                    // X reports key repeat as a release immediately followed by a press with the
+                    // same timestamp.
+                    let next_event = self.xcb.poll_for_queued_event()?;
+                    match next_event {
+                        Some(xcb::Event::X(x::Event::KeyPress(next)))
+                            if next.detail() == ev.detail() && next.time() == ev.time() =>
+                        {
+                            let key = self.keyboard.borrow().key_press(&next, true);
+                            self.gui.emit_key(key);
+                        }
+                        next_event => {
+                            queued_event = next_event;
+                            let key = self.keyboard.borrow().key_release(&ev);
+                            self.gui.emit_key(key);
+                        }
                     }
                 }
-                xcb::Event::X(x::Event::KeyPress(ev)) => {
-                    println!("Key '{}' pressed", ev.detail());
+                xcb::Event::Xkb(xkb::Event::StateNotify(ev)) => {
+                    self.keyboard.borrow_mut().update_state(&ev);
                 }
                 xcb::Event::X(x::Event::ClientMessage(ev)) => {
                     if let x::ClientMessageData::Data32([atom, ..]) = ev.data() {
@@ -326,8 +663,8 @@ impl Window {
                 _ => {}
             }
 
-            if self.gui.check_dirty() {
-                self.queue_redraw();
+            if self.gui.has_damage() {
+                self.redraw();
             }
             self.xcb.flush()?;
         }
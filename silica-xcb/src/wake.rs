@@ -0,0 +1,44 @@
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+};
+
+/// An eventfd that other threads write to, to wake the event loop out of `poll`.
+pub(crate) struct Wake(OwnedFd);
+
+impl Wake {
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Wake(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+    pub fn wake(&self) {
+        let value: u64 = 1;
+        unsafe {
+            libc::write(
+                self.0.as_raw_fd(),
+                &value as *const u64 as *const libc::c_void,
+                std::mem::size_of::<u64>(),
+            );
+        }
+    }
+    /// Resets the eventfd after it was reported readable.
+    pub fn clear(&self) {
+        let mut value: u64 = 0;
+        unsafe {
+            libc::read(
+                self.0.as_raw_fd(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                std::mem::size_of::<u64>(),
+            );
+        }
+    }
+}
+
+impl AsRawFd for Wake {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use taffy::prelude::*;
+
+use crate::{
+    model::{FloatModel, Model},
+    signal, Connection, Gui, PointerState, SignalFuture, Signals, StyleColor, Theme, TimerHandle,
+    VisualColors, VisualStyle, WidgetData, WidgetObject,
+};
+
+/// The time between animation frames.
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Maps the linear progress of an animation, from 0 to 1, to the progress of the animated value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+struct AnimationState {
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+    update: RefCell<Box<dyn FnMut(f32)>>,
+    running: Cell<bool>,
+    signals: Signals<Animation>,
+}
+
+/// A handle to a running animation, created with [`Gui::animate`] or one of the helpers built on
+/// it.
+#[derive(Clone)]
+pub struct Animation(Rc<AnimationState>);
+
+impl Animation {
+    /// Stops the animation where it is, without emitting [`Finished`](signal::Finished).
+    /// Returns `false` if it was no longer running.
+    pub fn cancel(&self) -> bool {
+        self.0.running.replace(false)
+    }
+    pub fn is_running(&self) -> bool {
+        self.0.running.get()
+    }
+    pub fn connect_finished<F>(&self, handler: F) -> Connection
+    where
+        F: FnMut(Animation, signal::Finished) + 'static,
+    {
+        self.0.signals.connect(handler)
+    }
+    /// Resolves when the animation runs to completion, for use in async code.
+    pub fn finished(&self) -> SignalFuture<signal::Finished> {
+        self.0.signals.next()
+    }
+
+    // Returns `false` once the animation is done.
+    fn step(&self, now: Instant) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        let elapsed = now.saturating_duration_since(self.0.start);
+        let t = if self.0.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.0.duration.as_secs_f32()
+        };
+        (self.0.update.borrow_mut())(self.0.easing.apply(t));
+        if t >= 1.0 && self.0.running.replace(false) {
+            self.0.signals.emit(self.clone(), signal::Finished);
+        }
+        self.is_running()
+    }
+}
+
+// A fade between the colors of two widget states.
+pub(crate) struct Transition {
+    animation: Animation,
+    colors: Rc<Cell<VisualColors>>,
+}
+
+impl Transition {
+    // The current colors, until the fade is done.
+    pub(crate) fn colors(&self) -> Option<VisualColors> {
+        self.animation.is_running().then(|| self.colors.get())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Animations {
+    running: Vec<Animation>,
+    frame_timer: Option<TimerHandle>,
+}
+
+impl Gui {
+    /// Calls `update` with the eased progress of the animation, from 0 to 1, once right away and
+    /// then on every frame until `duration` has passed. Each call is expected to change what is
+    /// drawn, which schedules the repaint.
+    pub fn animate<F>(&self, duration: Duration, easing: Easing, update: F) -> Animation
+    where
+        F: FnMut(f32) + 'static,
+    {
+        let animation = Animation(Rc::new(AnimationState {
+            start: self.now(),
+            duration,
+            easing,
+            update: RefCell::new(Box::new(update)),
+            running: Cell::new(true),
+            signals: Signals::new(),
+        }));
+        if animation.step(self.now()) {
+            let mut animations = self.animations.borrow_mut();
+            animations.running.push(animation.clone());
+            if animations.frame_timer.is_none() {
+                animations.frame_timer =
+                    Some(self.add_interval(FRAME_INTERVAL, |gui| gui.step_animations()));
+            }
+        }
+        animation
+    }
+    /// Whether any animation is running.
+    pub fn is_animating(&self) -> bool {
+        self.animations
+            .borrow()
+            .running
+            .iter()
+            .any(Animation::is_running)
+    }
+    /// Animates `model` from its current value to `to`.
+    pub fn animate_float(
+        &self,
+        model: Rc<FloatModel>,
+        to: f32,
+        duration: Duration,
+        easing: Easing,
+    ) -> Animation {
+        let from = model.get();
+        self.animate(duration, easing, move |t| {
+            model.clone().set(from + (to - from) * t);
+        })
+    }
+
+    fn step_animations(&self) {
+        let now = self.now();
+        let running = self.animations.borrow().running.clone();
+        for animation in running {
+            animation.step(now);
+        }
+        let mut animations = self.animations.borrow_mut();
+        animations.running.retain(Animation::is_running);
+        if animations.running.is_empty() {
+            if let Some(timer) = animations.frame_timer.take() {
+                timer.cancel();
+            }
+        }
+    }
+}
+
+fn lerp_dimension(from: Dimension, to: Dimension, t: f32) -> Dimension {
+    match (from, to) {
+        (Dimension::Points(from), Dimension::Points(to)) => {
+            Dimension::Points(from + (to - from) * t)
+        }
+        (Dimension::Percent(from), Dimension::Percent(to)) => {
+            Dimension::Percent(from + (to - from) * t)
+        }
+        _ if t < 1.0 => from,
+        _ => to,
+    }
+}
+
+fn lerp_size(from: Size<Dimension>, to: Size<Dimension>, t: f32) -> Size<Dimension> {
+    Size {
+        width: lerp_dimension(from.width, to.width, t),
+        height: lerp_dimension(from.height, to.height, t),
+    }
+}
+
+fn lerp_color(
+    from: Option<StyleColor>,
+    to: Option<StyleColor>,
+    t: f32,
+    theme: &Theme,
+) -> Option<StyleColor> {
+    match (from, to) {
+        // Finish on the exact target, so theme colors stay theme colors.
+        _ if t >= 1.0 => to,
+        (Some(from), Some(to)) => Some(from.resolve(theme).lerp(to.resolve(theme), t).into()),
+        _ => from,
+    }
+}
+
+fn lerp_colors(from: VisualColors, to: VisualColors, t: f32, theme: &Theme) -> VisualColors {
+    VisualColors {
+        background: lerp_color(from.background, to.background, t, theme),
+        border: lerp_color(from.border, to.border, t, theme),
+        foreground: lerp_color(from.foreground, to.foreground, t, theme),
+    }
+}
+
+impl<T> WidgetData<T> {
+    pub(crate) fn cancel_transition(&self) {
+        if let Some(transition) = self.transition.take() {
+            transition.animation.cancel();
+        }
+    }
+}
+
+impl<T: WidgetObject> WidgetData<T> {
+    // Called after the pointer state changed, with the colors drawn before. Fades to the new
+    // colors over the style's `transition`, or repaints at once.
+    pub(crate) fn transition_visual(&self, from: Option<VisualColors>, state: PointerState) {
+        self.cancel_transition();
+        self.queue_draw();
+        let Some(visual) = self.visual.borrow().clone() else {
+            return;
+        };
+        let (Some(from), to) = (from, visual.colors(self.visual_state())) else {
+            return;
+        };
+        if state == PointerState::Press || visual.transition.is_zero() || from == to {
+            return;
+        }
+        let colors = Rc::new(Cell::new(from));
+        // Theme colors are resolved on each frame, so switching themes mid-fade takes effect.
+        let gui = Rc::downgrade(&self.gui);
+        let this = self.this.clone();
+        let animation = self.gui.animate(visual.transition, Easing::EaseOut, {
+            let colors = colors.clone();
+            move |t| {
+                if let Some(gui) = gui.upgrade() {
+                    colors.set(lerp_colors(from, to, t, &gui.theme()));
+                }
+                if let Some(widget) = this.upgrade() {
+                    widget.queue_draw();
+                }
+            }
+        });
+        *self.transition.borrow_mut() = Some(Transition { animation, colors });
+    }
+}
+
+impl<T> WidgetData<T>
+where
+    T: WidgetObject + 'static,
+{
+    /// Animates the widget's colors to `target`, replacing any running visual animation. Colors
+    /// that are unset on either side switch at the end.
+    pub fn animate_visual(&self, target: VisualStyle, duration: Duration, easing: Easing) {
+        if let Some(animation) = self.visual_animation.take() {
+            animation.cancel();
+        }
+        let Some(from) = self.base_visual.borrow().clone() else {
+            self.set_visual(Some(target));
+            return;
+        };
+        let this = self.this.clone();
+        let gui = Rc::downgrade(&self.gui);
+        let animation = self.gui.animate(duration, easing, move |t| {
+            if let (Some(widget), Some(gui)) = (this.upgrade(), gui.upgrade()) {
+                let colors = lerp_colors(from.normal(), target.normal(), t, &gui.theme());
+                widget.set_visual(Some(VisualStyle {
+                    background: colors.background,
+                    border: colors.border,
+                    foreground: colors.foreground,
+                    ..target.clone()
+                }));
+            }
+        });
+        *self.visual_animation.borrow_mut() = Some(animation);
+    }
+    /// Sets the widget's layout to `target`, animating `size`, `min_size` and `max_size` and
+    /// replacing any running layout animation. Other fields take effect immediately.
+    pub fn animate_layout(&self, target: Style, duration: Duration, easing: Easing) {
+        if let Some(animation) = self.layout_animation.take() {
+            animation.cancel();
+        }
+        let from = self.layout();
+        let this = self.this.clone();
+        let animation = self.gui.animate(duration, easing, move |t| {
+            if let Some(widget) = this.upgrade() {
+                widget.set_layout(Style {
+                    size: lerp_size(from.size, target.size, t),
+                    min_size: lerp_size(from.min_size, target.min_size, t),
+                    max_size: lerp_size(from.max_size, target.max_size, t),
+                    ..target.clone()
+                });
+            }
+        });
+        *self.layout_animation.borrow_mut() = Some(animation);
+    }
+}
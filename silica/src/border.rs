@@ -0,0 +1,154 @@
+use taffy::{geometry::Point, prelude::*};
+
+use crate::BorderStyle;
+
+/// Resolves border widths for a box of `size`. Percentages of the left and right sides are of
+/// the width, those of the top and bottom of the height. Widths that add up to more than the
+/// box are scaled down to fit.
+pub fn resolve_border(size: Size<f32>, border: Rect<LengthPercentage>) -> Rect<f32> {
+    let resolve = |value: LengthPercentage, length: f32| match value {
+        LengthPercentage::Points(points) => points.max(0.0),
+        LengthPercentage::Percent(percent) => (percent * length).max(0.0),
+    };
+    let fit = |a: f32, b: f32, length: f32| {
+        let length = length.max(0.0);
+        if a + b > length {
+            let scale = length / (a + b);
+            (a * scale, b * scale)
+        } else {
+            (a, b)
+        }
+    };
+    let (left, right) = fit(
+        resolve(border.left, size.width),
+        resolve(border.right, size.width),
+        size.width,
+    );
+    let (top, bottom) = fit(
+        resolve(border.top, size.height),
+        resolve(border.bottom, size.height),
+        size.height,
+    );
+    Rect {
+        left,
+        right,
+        top,
+        bottom,
+    }
+}
+
+/// The area each side of a border covers inside a box of `size`: a rectangle, or a trapezoid
+/// where it meets a side that has a width. Adjacent sides meet on the line from an outer corner to
+/// the matching inner corner. Points are clockwise, starting at the outer corners. `border` must
+/// fit, see [`resolve_border`].
+pub fn border_quads(size: Size<f32>, border: Rect<f32>) -> Rect<[Point<f32>; 4]> {
+    let (width, height) = (size.width, size.height);
+    let (inner_left, inner_right) = (border.left, width - border.right);
+    let (inner_top, inner_bottom) = (border.top, height - border.bottom);
+    let point = |x, y| Point { x, y };
+    Rect {
+        top: [
+            point(0.0, 0.0),
+            point(width, 0.0),
+            point(inner_right, inner_top),
+            point(inner_left, inner_top),
+        ],
+        right: [
+            point(width, 0.0),
+            point(width, height),
+            point(inner_right, inner_bottom),
+            point(inner_right, inner_top),
+        ],
+        bottom: [
+            point(width, height),
+            point(0.0, height),
+            point(inner_left, inner_bottom),
+            point(inner_right, inner_bottom),
+        ],
+        left: [
+            point(0.0, height),
+            point(0.0, 0.0),
+            point(inner_left, inner_top),
+            point(inner_left, inner_bottom),
+        ],
+    }
+}
+
+/// The region each side of a border owns inside a box of `size`, for clipping borders with
+/// rounded corners. The joins of [`border_quads`] are extended to the middle of the box, where
+/// the wedges meet. Points are clockwise, starting at the outer corners.
+pub fn border_wedges(size: Size<f32>, border: Rect<f32>) -> Rect<[Point<f32>; 5]> {
+    let (width, height) = (size.width, size.height);
+    let center = Point {
+        x: width / 2.0,
+        y: height / 2.0,
+    };
+    let join = |x: f32, y: f32, dx: f32, dy: f32| {
+        let (dx, dy) = if dx == 0.0 && dy == 0.0 {
+            (1.0, 1.0)
+        } else {
+            (dx, dy)
+        };
+        let scale = (center.x / dx.abs()).min(center.y / dy.abs());
+        Point {
+            x: x + dx * scale,
+            y: y + dy * scale,
+        }
+    };
+    let top_left = join(0.0, 0.0, border.left, border.top);
+    let top_right = join(width, 0.0, -border.right, border.top);
+    let bottom_right = join(width, height, -border.right, -border.bottom);
+    let bottom_left = join(0.0, height, border.left, -border.bottom);
+    let point = |x, y| Point { x, y };
+    Rect {
+        top: [
+            point(0.0, 0.0),
+            point(width, 0.0),
+            top_right,
+            center,
+            top_left,
+        ],
+        right: [
+            point(width, 0.0),
+            point(width, height),
+            bottom_right,
+            center,
+            top_right,
+        ],
+        bottom: [
+            point(width, height),
+            point(0.0, height),
+            bottom_left,
+            center,
+            bottom_right,
+        ],
+        left: [
+            point(0.0, height),
+            point(0.0, 0.0),
+            top_left,
+            center,
+            bottom_left,
+        ],
+    }
+}
+
+/// The stretches of a side `length` long that a border of `style` and `width` paints, as start
+/// and end offsets from the top or left of the box. Dashes are three widths on and two off, dots
+/// one on and one off, both starting at the corner. Solid borders paint the whole side.
+pub fn border_dashes(length: f32, width: f32, style: BorderStyle) -> Vec<(f32, f32)> {
+    let (on, period) = match style {
+        BorderStyle::Solid => return vec![(0.0, length)],
+        BorderStyle::Dashed => (width * 3.0, width * 5.0),
+        BorderStyle::Dotted => (width, width * 2.0),
+    };
+    if period <= 0.0 {
+        return Vec::new();
+    }
+    let count = (length / period).ceil().max(0.0) as usize;
+    (0..count)
+        .map(|i| {
+            let start = i as f32 * period;
+            (start, (start + on).min(length))
+        })
+        .collect()
+}
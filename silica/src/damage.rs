@@ -0,0 +1,120 @@
+use taffy::geometry::{Point, Size};
+
+// Past this many rectangles a region collapses into its bounding box, which is usually cheaper
+// to repaint than many small areas.
+const MAX_RECTS: usize = 16;
+
+/// An axis-aligned rectangle in window coordinates.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DamageRect {
+    pub origin: Point<f32>,
+    pub size: Size<f32>,
+}
+
+impl DamageRect {
+    pub fn new(origin: Point<f32>, size: Size<f32>) -> Self {
+        DamageRect { origin, size }
+    }
+    pub fn right(&self) -> f32 {
+        self.origin.x + self.size.width
+    }
+    pub fn bottom(&self) -> f32 {
+        self.origin.y + self.size.height
+    }
+    pub fn is_empty(&self) -> bool {
+        self.size.width <= 0.0 || self.size.height <= 0.0
+    }
+    /// Grows the rectangle to whole pixels.
+    pub fn round_out(&self) -> Self {
+        let x = self.origin.x.floor();
+        let y = self.origin.y.floor();
+        DamageRect {
+            origin: Point { x, y },
+            size: Size {
+                width: self.right().ceil() - x,
+                height: self.bottom().ceil() - y,
+            },
+        }
+    }
+    pub fn intersects(&self, other: &DamageRect) -> bool {
+        self.origin.x < other.right()
+            && other.origin.x < self.right()
+            && self.origin.y < other.bottom()
+            && other.origin.y < self.bottom()
+    }
+    pub fn intersection(&self, other: &DamageRect) -> Option<DamageRect> {
+        let x = self.origin.x.max(other.origin.x);
+        let y = self.origin.y.max(other.origin.y);
+        let rect = DamageRect {
+            origin: Point { x, y },
+            size: Size {
+                width: self.right().min(other.right()) - x,
+                height: self.bottom().min(other.bottom()) - y,
+            },
+        };
+        (!rect.is_empty()).then_some(rect)
+    }
+    pub fn union(&self, other: &DamageRect) -> DamageRect {
+        let x = self.origin.x.min(other.origin.x);
+        let y = self.origin.y.min(other.origin.y);
+        DamageRect {
+            origin: Point { x, y },
+            size: Size {
+                width: self.right().max(other.right()) - x,
+                height: self.bottom().max(other.bottom()) - y,
+            },
+        }
+    }
+    // Touching rectangles are merged too, so adjacent widgets form a single area.
+    fn touches(&self, other: &DamageRect) -> bool {
+        self.origin.x <= other.right()
+            && other.origin.x <= self.right()
+            && self.origin.y <= other.bottom()
+            && other.origin.y <= self.bottom()
+    }
+}
+
+/// A set of damaged areas in window coordinates. Overlapping or touching rectangles are merged
+/// as they are added.
+#[derive(Clone, Default, Debug)]
+pub struct DamageRegion {
+    rects: Vec<DamageRect>,
+}
+
+impl DamageRegion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+    pub fn rects(&self) -> &[DamageRect] {
+        &self.rects
+    }
+    pub fn add(&mut self, rect: DamageRect) {
+        if rect.is_empty() {
+            return;
+        }
+        let mut rect = rect.round_out();
+        // Merging can make the union touch rectangles that were checked before, so repeat
+        // until nothing changes.
+        while let Some(index) = self.rects.iter().position(|other| other.touches(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+        if self.rects.len() > MAX_RECTS {
+            let bounds = self.bounds().unwrap();
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+    }
+    pub fn bounds(&self) -> Option<DamageRect> {
+        self.rects.iter().copied().reduce(|a, b| a.union(&b))
+    }
+    pub fn intersects(&self, rect: &DamageRect) -> bool {
+        self.rects.iter().any(|other| other.intersects(rect))
+    }
+}
@@ -0,0 +1,41 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{Signal, Widget};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventPhase {
+    /// The event is travelling from the root down to the target's parent.
+    Capture,
+    /// The event has reached the widget it was dispatched to.
+    Target,
+    /// The event is travelling from the target's parent back up to the root.
+    Bubble,
+}
+
+/// A signal dispatched through the widget tree by [`Gui::dispatch_event`](crate::Gui::dispatch_event).
+#[derive(Clone)]
+pub struct Event<S> {
+    pub signal: S,
+    pub(crate) phase: EventPhase,
+    pub(crate) target: Widget,
+    pub(crate) stopped: Rc<Cell<bool>>,
+}
+
+impl<S> Signal for Event<S> where S: Signal {}
+
+impl<S> Event<S> {
+    pub fn phase(&self) -> EventPhase {
+        self.phase
+    }
+    pub fn target(&self) -> &Widget {
+        &self.target
+    }
+    /// Stops the event from reaching any further widgets. Other handlers on the current widget
+    /// are still called.
+    pub fn stop_propagation(&self) {
+        self.stopped.set(true);
+    }
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.stopped.get()
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Wake, Waker},
+};
+
+use crate::{Gui, GuiProxy};
+
+// Wakers can be sent to other threads, so waking goes through the gui's proxy queue, which also
+// wakes the backend's event loop.
+struct TaskWaker {
+    id: u64,
+    proxy: GuiProxy,
+    // Set while a poll is queued, so repeated wakes queue a single poll.
+    scheduled: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::AcqRel) {
+            let id = self.id;
+            self.proxy.post(move |gui| gui.poll_task(id));
+        }
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>,
+}
+
+#[derive(Default)]
+pub(crate) struct Tasks {
+    next_id: u64,
+    tasks: HashMap<u64, Task>,
+    // The task being polled, which is not in `tasks`, and whether it was cancelled meanwhile.
+    polling: Option<(u64, bool)>,
+}
+
+/// Handle to a task started with [`Gui::spawn_local`].
+pub struct TaskHandle {
+    tasks: Weak<RefCell<Tasks>>,
+    id: u64,
+}
+
+impl TaskHandle {
+    /// Drops the task's future without polling it again. Returns `false` if the task had already
+    /// finished or been cancelled.
+    pub fn cancel(&self) -> bool {
+        let Some(tasks) = self.tasks.upgrade() else {
+            return false;
+        };
+        // The future may own captures with their own `Drop`, so drop it after the borrow ends.
+        let removed = {
+            let mut tasks = tasks.borrow_mut();
+            match tasks.polling {
+                Some((id, ref mut cancelled)) if id == self.id => {
+                    let was_pending = !*cancelled;
+                    *cancelled = true;
+                    return was_pending;
+                }
+                _ => tasks.tasks.remove(&self.id),
+            }
+        };
+        removed.is_some()
+    }
+    /// Whether the task has neither finished nor been cancelled.
+    pub fn is_pending(&self) -> bool {
+        self.tasks
+            .upgrade()
+            .map(|tasks| {
+                let tasks = tasks.borrow();
+                tasks.tasks.contains_key(&self.id) || tasks.polling == Some((self.id, false))
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl Gui {
+    /// Runs `future` on the GUI thread. It is first polled between events, like closures posted
+    /// through a [`GuiProxy`], and again each time its waker is woken.
+    ///
+    /// The `Gui` owns the future until it completes or is cancelled through the returned handle.
+    /// A future that captures an `Rc<Gui>` and never completes therefore keeps the `Gui` alive;
+    /// capture a `Weak<Gui>` instead, or cancel the task when it is no longer needed.
+    pub fn spawn_local<F>(&self, future: F) -> TaskHandle
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let (id, waker) = {
+            let mut tasks = self.tasks.borrow_mut();
+            let id = tasks.next_id;
+            tasks.next_id += 1;
+            let waker = Arc::new(TaskWaker {
+                id,
+                proxy: self.proxy(),
+                scheduled: AtomicBool::new(false),
+            });
+            tasks.tasks.insert(
+                id,
+                Task {
+                    future: Box::pin(future),
+                    waker: waker.clone(),
+                },
+            );
+            (id, waker)
+        };
+        waker.wake();
+        TaskHandle {
+            tasks: Rc::downgrade(&self.tasks),
+            id,
+        }
+    }
+    fn poll_task(self: &Rc<Self>, id: u64) {
+        // The task is taken out while polling, so that it can spawn other tasks.
+        let Some(mut task) = self.tasks.borrow_mut().tasks.remove(&id) else {
+            return;
+        };
+        self.tasks.borrow_mut().polling = Some((id, false));
+        task.waker.scheduled.store(false, Ordering::Release);
+        let waker = Waker::from(task.waker.clone());
+        let mut context = Context::from_waker(&waker);
+        let pending = task.future.as_mut().poll(&mut context).is_pending();
+        let mut tasks = self.tasks.borrow_mut();
+        let cancelled = matches!(tasks.polling.take(), Some((_, true)));
+        // Otherwise `task` is dropped after the borrow ends.
+        if pending && !cancelled {
+            tasks.tasks.insert(id, task);
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use std::time::Duration;
+
 use taffy::{geometry::Point, prelude::*};
 
-#[derive(Clone, Copy, Debug)]
+use crate::{border_quads, resolve_border, Color, Theme};
+
+/// A color role whose value comes from the active [`Theme`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ThemeColor {
     Background,
     ButtonNormal,
@@ -9,42 +14,349 @@ pub enum ThemeColor {
     ButtonDisable,
     Border,
     Foreground,
+    ForegroundDisable,
 }
 
 impl ThemeColor {
-    pub fn to_rgba(self) -> [f32; 4] {
+    pub const ALL: [ThemeColor; 8] = [
+        ThemeColor::Background,
+        ThemeColor::ButtonNormal,
+        ThemeColor::ButtonOver,
+        ThemeColor::ButtonPress,
+        ThemeColor::ButtonDisable,
+        ThemeColor::Border,
+        ThemeColor::Foreground,
+        ThemeColor::ForegroundDisable,
+    ];
+
+    /// The snake case name used for the role in theme files.
+    pub fn name(self) -> &'static str {
         match self {
-            ThemeColor::Background => [0.847, 0.847, 0.847, 1.0],
-            ThemeColor::ButtonNormal | ThemeColor::ButtonDisable => [0.784, 0.784, 0.784, 1.0],
-            ThemeColor::ButtonOver => [0.722, 0.722, 0.722, 1.0],
-            ThemeColor::ButtonPress => [0.659, 0.659, 0.659, 1.0],
-            ThemeColor::Border => [0.094, 0.094, 0.094, 1.0],
-            ThemeColor::Foreground => [0.094, 0.094, 0.094, 1.0],
+            ThemeColor::Background => "background",
+            ThemeColor::ButtonNormal => "button_normal",
+            ThemeColor::ButtonOver => "button_over",
+            ThemeColor::ButtonPress => "button_press",
+            ThemeColor::ButtonDisable => "button_disable",
+            ThemeColor::Border => "border",
+            ThemeColor::Foreground => "foreground",
+            ThemeColor::ForegroundDisable => "foreground_disable",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<ThemeColor> {
+        ThemeColor::ALL.into_iter().find(|role| role.name() == name)
+    }
+    /// Looks the color up in `theme`.
+    pub fn resolve(self, theme: &Theme) -> Color {
+        theme.color(self)
+    }
+    /// The RGBA components of the color in `theme`, usually [`Gui::theme`](crate::Gui::theme).
+    pub fn to_rgba(self, theme: &Theme) -> [f32; 4] {
+        self.resolve(theme).to_array()
+    }
+}
+
+/// A color used for drawing: either a theme role or a literal color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StyleColor {
+    Theme(ThemeColor),
+    Color(Color),
+}
+
+impl StyleColor {
+    pub fn resolve(self, theme: &Theme) -> Color {
+        match self {
+            StyleColor::Theme(color) => color.resolve(theme),
+            StyleColor::Color(color) => color,
+        }
+    }
+}
+
+impl From<ThemeColor> for StyleColor {
+    fn from(color: ThemeColor) -> Self {
+        StyleColor::Theme(color)
+    }
+}
+
+impl From<Color> for StyleColor {
+    fn from(color: Color) -> Self {
+        StyleColor::Color(color)
+    }
+}
+
+/// The colors a widget is drawn with. In a [`VisualStyle`] state override, `None` keeps the color
+/// from the states below it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VisualColors {
+    pub background: Option<StyleColor>,
+    pub border: Option<StyleColor>,
+    pub foreground: Option<StyleColor>,
+}
+
+impl VisualColors {
+    pub const NONE: VisualColors = VisualColors {
+        background: None,
+        border: None,
+        foreground: None,
+    };
+
+    /// Returns `self` with unset colors taken from `base`.
+    pub fn or(self, base: VisualColors) -> VisualColors {
+        VisualColors {
+            background: self.background.or(base.background),
+            border: self.border.or(base.border),
+            foreground: self.foreground.or(base.foreground),
         }
     }
 }
 
-#[derive(Clone)]
+/// The radius of each corner of a widget's background and border.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CornerRadius {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadius {
+    pub const ZERO: CornerRadius = CornerRadius::all(0.0);
+
+    pub const fn all(radius: f32) -> CornerRadius {
+        CornerRadius {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+    pub fn is_zero(&self) -> bool {
+        *self == CornerRadius::ZERO
+    }
+    /// Scales the radii down so that corners on the same side of a box of `size` don't overlap,
+    /// like CSS does.
+    pub fn clamp(self, size: Size<f32>) -> CornerRadius {
+        let ratio = |length: f32, a: f32, b: f32| {
+            if a + b > 0.0 {
+                length / (a + b)
+            } else {
+                1.0
+            }
+        };
+        let scale = 1f32
+            .min(ratio(size.width, self.top_left, self.top_right))
+            .min(ratio(size.width, self.bottom_left, self.bottom_right))
+            .min(ratio(size.height, self.top_left, self.bottom_left))
+            .min(ratio(size.height, self.top_right, self.bottom_right))
+            .max(0.0);
+        CornerRadius {
+            top_left: self.top_left.max(0.0) * scale,
+            top_right: self.top_right.max(0.0) * scale,
+            bottom_right: self.bottom_right.max(0.0) * scale,
+            bottom_left: self.bottom_left.max(0.0) * scale,
+        }
+    }
+}
+
+impl From<f32> for CornerRadius {
+    fn from(radius: f32) -> Self {
+        CornerRadius::all(radius)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, from 0 to 1.
+    pub offset: f32,
+    pub color: StyleColor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    /// Along a line through the center, at `angle` degrees clockwise from pointing up, like CSS
+    /// `linear-gradient`. The line is long enough for the end colors to reach the corners.
+    Linear { angle: f32 },
+    /// From the center out to the farthest corner.
+    Radial,
+}
+
+/// A background that blends between colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+    pub fn linear<C>(angle: f32, stops: impl IntoIterator<Item = (f32, C)>) -> Gradient
+    where
+        C: Into<StyleColor>,
+    {
+        Gradient::new(GradientShape::Linear { angle }, stops)
+    }
+    pub fn radial<C>(stops: impl IntoIterator<Item = (f32, C)>) -> Gradient
+    where
+        C: Into<StyleColor>,
+    {
+        Gradient::new(GradientShape::Radial, stops)
+    }
+    fn new<C>(shape: GradientShape, stops: impl IntoIterator<Item = (f32, C)>) -> Gradient
+    where
+        C: Into<StyleColor>,
+    {
+        let stops = stops
+            .into_iter()
+            .map(|(offset, color)| GradientStop {
+                offset,
+                color: color.into(),
+            })
+            .collect();
+        Gradient { shape, stops }
+    }
+}
+
+/// A drop shadow drawn behind a widget, following its corner radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub offset: Point<f32>,
+    /// Width of the soft edge, centered on the outline of the shadow.
+    pub blur: f32,
+    /// How far the shadow extends beyond the widget on every side before blurring.
+    pub spread: f32,
+    pub color: StyleColor,
+}
+
+impl Shadow {
+    /// How far the shadow reaches beyond each side of the widget.
+    pub fn outset(&self) -> Rect<f32> {
+        let reach = (self.spread + self.blur / 2.0).max(0.0);
+        Rect {
+            left: (reach - self.offset.x).max(0.0),
+            right: (reach + self.offset.x).max(0.0),
+            top: (reach - self.offset.y).max(0.0),
+            bottom: (reach + self.offset.y).max(0.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+/// The widget state that selects which [`VisualStyle`] overrides apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VisualState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+    pub disabled: bool,
+    pub checked: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct VisualStyle {
-    pub background: Option<ThemeColor>,
-    pub border: Option<ThemeColor>,
-    pub foreground: Option<ThemeColor>,
+    pub background: Option<StyleColor>,
+    pub border: Option<StyleColor>,
+    pub foreground: Option<StyleColor>,
+    pub hover: VisualColors,
+    pub pressed: VisualColors,
+    pub focused: VisualColors,
+    pub disabled: VisualColors,
+    pub checked: VisualColors,
+    /// How long the colors take to fade when the pointer enters or leaves the widget. Pressing
+    /// shows at once.
+    pub transition: Duration,
+    pub corner_radius: CornerRadius,
+    /// Painted over the background color, in every state.
+    pub gradient: Option<Gradient>,
+    pub shadow: Option<Shadow>,
+    /// Colors of individual border sides, used instead of the border color of every state.
+    pub border_sides: Rect<Option<StyleColor>>,
+    pub border_style: BorderStyle,
 }
 
 impl VisualStyle {
     pub const BUTTON: VisualStyle = VisualStyle {
-        background: Some(ThemeColor::ButtonNormal),
-        border: Some(ThemeColor::Border),
-        foreground: Some(ThemeColor::Foreground),
+        background: Some(StyleColor::Theme(ThemeColor::ButtonNormal)),
+        border: Some(StyleColor::Theme(ThemeColor::Border)),
+        foreground: Some(StyleColor::Theme(ThemeColor::Foreground)),
+        hover: VisualColors {
+            background: Some(StyleColor::Theme(ThemeColor::ButtonOver)),
+            ..VisualColors::NONE
+        },
+        pressed: VisualColors {
+            background: Some(StyleColor::Theme(ThemeColor::ButtonPress)),
+            ..VisualColors::NONE
+        },
+        focused: VisualColors::NONE,
+        disabled: VisualColors {
+            background: Some(StyleColor::Theme(ThemeColor::ButtonDisable)),
+            border: Some(StyleColor::Theme(ThemeColor::ForegroundDisable)),
+            foreground: Some(StyleColor::Theme(ThemeColor::ForegroundDisable)),
+        },
+        checked: VisualColors::NONE,
+        transition: Duration::from_millis(120),
+        corner_radius: CornerRadius::ZERO,
+        gradient: None,
+        shadow: None,
+        border_sides: VisualStyle::NO_BORDER_SIDES,
+        border_style: BorderStyle::Solid,
     };
+    const NO_BORDER_SIDES: Rect<Option<StyleColor>> = Rect {
+        left: None,
+        right: None,
+        top: None,
+        bottom: None,
+    };
+
+    /// The colors without any state override.
+    pub fn normal(&self) -> VisualColors {
+        VisualColors {
+            background: self.background,
+            border: self.border,
+            foreground: self.foreground,
+        }
+    }
+    /// The colors for `state`. Overrides apply in the order checked, focused, hover, pressed,
+    /// disabled, each taking precedence over the ones before.
+    pub fn colors(&self, state: VisualState) -> VisualColors {
+        [
+            (state.checked, self.checked),
+            (state.focused, self.focused),
+            (state.hovered, self.hover),
+            (state.pressed, self.pressed),
+            (state.disabled, self.disabled),
+        ]
+        .into_iter()
+        .filter(|(active, _)| *active)
+        .fold(self.normal(), |colors, (_, overrides)| overrides.or(colors))
+    }
 }
 
 impl Default for VisualStyle {
     fn default() -> Self {
         VisualStyle {
             background: None,
-            border: Some(ThemeColor::Border),
-            foreground: Some(ThemeColor::Foreground),
+            border: Some(ThemeColor::Border.into()),
+            foreground: Some(ThemeColor::Foreground.into()),
+            hover: VisualColors::NONE,
+            pressed: VisualColors::NONE,
+            focused: VisualColors::NONE,
+            disabled: VisualColors {
+                border: Some(ThemeColor::ForegroundDisable.into()),
+                foreground: Some(ThemeColor::ForegroundDisable.into()),
+                ..VisualColors::NONE
+            },
+            checked: VisualColors::NONE,
+            transition: Duration::ZERO,
+            corner_radius: CornerRadius::ZERO,
+            gradient: None,
+            shadow: None,
+            border_sides: VisualStyle::NO_BORDER_SIDES,
+            border_style: BorderStyle::Solid,
         }
     }
 }
@@ -88,9 +400,61 @@ pub trait GraphicsContext {
     fn save(&mut self);
     fn restore(&mut self);
     fn translate(&mut self, tx: f32, ty: f32);
+    /// Intersects the clip region with a rectangle. The clip is restored by [`restore`](Self::restore).
+    fn clip_rect(&mut self, point: Point<f32>, size: Size<f32>);
+    /// Intersects the clip region with a rectangle with rounded corners.
+    fn clip_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: f32);
 
-    fn set_color(&mut self, color: ThemeColor);
-    fn draw_border(&mut self, size: Size<f32>, border: Rect<LengthPercentage>);
+    /// Sets the color for the following drawing. Backends look theme roles up in
+    /// [`Gui::theme`](crate::Gui::theme).
+    fn set_color(&mut self, color: StyleColor);
+    /// Fills each side of a border inside `size` with the current color, using
+    /// [`resolve_border`] and [`border_quads`] so that every backend draws the same shape.
+    fn draw_border(&mut self, size: Size<f32>, border: Rect<LengthPercentage>) {
+        let border = resolve_border(size, border);
+        let quads = border_quads(size, border);
+        let sides = [
+            (border.top, &quads.top),
+            (border.right, &quads.right),
+            (border.bottom, &quads.bottom),
+            (border.left, &quads.left),
+        ];
+        let polygons: Vec<&[Point<f32>]> = sides
+            .into_iter()
+            .filter(|(width, _)| *width > 0.0)
+            .map(|(_, quad)| quad.as_slice())
+            .collect();
+        self.fill_polygons(&polygons);
+    }
+    /// Draws a border inside `size` with a color per side, skipping sides without a color.
+    /// Sides meet diagonally at the corners.
+    fn draw_border_sides(
+        &mut self,
+        size: Size<f32>,
+        border: Rect<f32>,
+        colors: Rect<Option<StyleColor>>,
+        radius: CornerRadius,
+        style: BorderStyle,
+    );
     fn draw_rect(&mut self, point: Point<f32>, size: Size<f32>);
+    /// Fills convex polygons as one shape, so that no seam shows where they share an edge.
+    fn fill_polygons(&mut self, polygons: &[&[Point<f32>]]);
+    fn draw_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: CornerRadius);
+    /// Fills a rectangle with rounded corners with `gradient`, ignoring the current color.
+    fn draw_gradient(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        gradient: &Gradient,
+    );
+    /// Draws the shadow cast by a rectangle with rounded corners, ignoring the current color.
+    fn draw_shadow(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        shadow: &Shadow,
+    );
     fn draw_text(&mut self, point: Point<f32>, size: Size<f32>, text: &TextSection);
 }
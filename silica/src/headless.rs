@@ -0,0 +1,476 @@
+use std::rc::Rc;
+
+use taffy::{geometry::Point, prelude::*};
+
+use crate::{
+    border_dashes, border_wedges, BorderStyle, Color, CornerRadius, Gradient, GradientShape,
+    GraphicsContext, Shadow, StyleColor, TextSection, Theme,
+};
+
+// Samples per pixel along each axis.
+const SAMPLES: usize = 4;
+
+/// A [`GraphicsContext`] that rasterizes into memory, for tests and for rendering without a
+/// window. Edges are antialiased by supersampling. Text isn't drawn, and dashed and dotted borders
+/// follow the straight sides only, like the Cairo backend.
+pub struct HeadlessContext {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    theme: Rc<Theme>,
+    state: State,
+    saved: Vec<State>,
+}
+
+#[derive(Clone)]
+struct State {
+    offset: Point<f32>,
+    color: Color,
+    // In pixel coordinates, like everything below.
+    clip: Vec<Shape>,
+}
+
+#[derive(Clone)]
+enum Shape {
+    // The union of convex polygons.
+    Polygons(Vec<Vec<Point<f32>>>),
+    RoundedRect {
+        origin: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+    },
+}
+
+impl Shape {
+    fn rect(origin: Point<f32>, size: Size<f32>, radius: CornerRadius) -> Shape {
+        Shape::RoundedRect {
+            origin,
+            size,
+            radius: radius.clamp(size),
+        }
+    }
+    fn contains(&self, point: Point<f32>) -> bool {
+        match self {
+            Shape::Polygons(polygons) => polygons
+                .iter()
+                .any(|points| polygon_contains(points, point)),
+            Shape::RoundedRect {
+                origin,
+                size,
+                radius,
+            } => {
+                let (x, y) = (point.x - origin.x, point.y - origin.y);
+                let (width, height) = (size.width, size.height);
+                if x < 0.0 || y < 0.0 || x >= width || y >= height {
+                    return false;
+                }
+                let outside_corner = |r: f32, cx: f32, cy: f32, in_corner: bool| {
+                    in_corner && (x - cx).powi(2) + (y - cy).powi(2) > r * r
+                };
+                let CornerRadius {
+                    top_left,
+                    top_right,
+                    bottom_right,
+                    bottom_left,
+                } = *radius;
+                !(outside_corner(top_left, top_left, top_left, x < top_left && y < top_left)
+                    || outside_corner(
+                        top_right,
+                        width - top_right,
+                        top_right,
+                        x > width - top_right && y < top_right,
+                    )
+                    || outside_corner(
+                        bottom_right,
+                        width - bottom_right,
+                        height - bottom_right,
+                        x > width - bottom_right && y > height - bottom_right,
+                    )
+                    || outside_corner(
+                        bottom_left,
+                        bottom_left,
+                        height - bottom_left,
+                        x < bottom_left && y > height - bottom_left,
+                    ))
+            }
+        }
+    }
+    fn translate(&self, offset: Point<f32>) -> Shape {
+        match self {
+            Shape::Polygons(polygons) => Shape::Polygons(
+                polygons
+                    .iter()
+                    .map(|points| {
+                        points
+                            .iter()
+                            .map(|point| Point {
+                                x: point.x + offset.x,
+                                y: point.y + offset.y,
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            Shape::RoundedRect {
+                origin,
+                size,
+                radius,
+            } => Shape::RoundedRect {
+                origin: Point {
+                    x: origin.x + offset.x,
+                    y: origin.y + offset.y,
+                },
+                size: *size,
+                radius: *radius,
+            },
+        }
+    }
+}
+
+// Points on an edge belong to the polygon on only one side of it, so polygons that share an edge,
+// like the sides of a border, cover every sample exactly once.
+fn polygon_contains(points: &[Point<f32>], point: Point<f32>) -> bool {
+    let edges = || points.iter().zip(points.iter().cycle().skip(1));
+    let area: f32 = edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+    if area == 0.0 {
+        return false;
+    }
+    edges().all(|(a, b)| {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        if dx == 0.0 && dy == 0.0 {
+            return true;
+        }
+        let cross = (dx * (point.y - a.y) - dy * (point.x - a.x)) * area.signum();
+        cross > 0.0 || (cross == 0.0 && (dy > 0.0 || (dy == 0.0 && dx < 0.0)))
+    })
+}
+
+impl HeadlessContext {
+    /// Creates a transparent image of `width` × `height` pixels, resolving theme colors in
+    /// `theme`.
+    pub fn new(width: u32, height: u32, theme: Rc<Theme>) -> HeadlessContext {
+        HeadlessContext {
+            width,
+            height,
+            pixels: vec![Color::TRANSPARENT; (width * height) as usize],
+            theme,
+            state: State {
+                offset: Point::ZERO,
+                color: Color::BLACK,
+                clip: Vec::new(),
+            },
+            saved: Vec::new(),
+        }
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+    /// The image as rows of 8-bit RGBA, with straight alpha.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| color.to_rgba8())
+            .collect()
+    }
+
+    fn shape(&self, shape: Shape) -> Shape {
+        shape.translate(self.state.offset)
+    }
+    // Composites `paint` over the pixels within `bounds`, averaging it over the samples of each
+    // pixel. `paint` gets pixel coordinates and returns `None` where it doesn't draw.
+    fn fill<F>(&mut self, origin: Point<f32>, size: Size<f32>, paint: F)
+    where
+        F: Fn(Point<f32>) -> Option<Color>,
+    {
+        let x0 = origin.x.floor().max(0.0) as u32;
+        let y0 = origin.y.floor().max(0.0) as u32;
+        let x1 = ((origin.x + size.width).ceil().max(0.0) as u32).min(self.width);
+        let y1 = ((origin.y + size.height).ceil().max(0.0) as u32).min(self.height);
+        let count = (SAMPLES * SAMPLES) as f32;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut sum = [0.0; 4];
+                for i in 0..SAMPLES * SAMPLES {
+                    let sample = Point {
+                        x: x as f32 + ((i % SAMPLES) as f32 + 0.5) / SAMPLES as f32,
+                        y: y as f32 + ((i / SAMPLES) as f32 + 0.5) / SAMPLES as f32,
+                    };
+                    if !self.state.clip.iter().all(|clip| clip.contains(sample)) {
+                        continue;
+                    }
+                    if let Some(color) = paint(sample) {
+                        sum[0] += color.r * color.a;
+                        sum[1] += color.g * color.a;
+                        sum[2] += color.b * color.a;
+                        sum[3] += color.a;
+                    }
+                }
+                if sum[3] > 0.0 {
+                    let pixel = &mut self.pixels[(y * self.width + x) as usize];
+                    *pixel = over(sum.map(|v| v / count), *pixel);
+                }
+            }
+        }
+    }
+    fn fill_shape(&mut self, shape: Shape, color: Color) {
+        let (origin, size) = bounds(&shape);
+        self.fill(origin, size, |point| shape.contains(point).then_some(color));
+    }
+}
+
+// Source over destination, with the source premultiplied.
+fn over(source: [f32; 4], destination: Color) -> Color {
+    let alpha = source[3] + destination.a * (1.0 - source[3]);
+    if alpha <= 0.0 {
+        return Color::TRANSPARENT;
+    }
+    let blend = |s: f32, d: f32| (s + d * destination.a * (1.0 - source[3])) / alpha;
+    Color::rgba(
+        blend(source[0], destination.r),
+        blend(source[1], destination.g),
+        blend(source[2], destination.b),
+        alpha,
+    )
+}
+
+fn bounds(shape: &Shape) -> (Point<f32>, Size<f32>) {
+    match shape {
+        Shape::Polygons(polygons) => {
+            let points = || polygons.iter().flatten();
+            let min_x = points().map(|p| p.x).fold(f32::INFINITY, f32::min);
+            let min_y = points().map(|p| p.y).fold(f32::INFINITY, f32::min);
+            let max_x = points().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+            let max_y = points().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+            (
+                Point { x: min_x, y: min_y },
+                Size {
+                    width: max_x - min_x,
+                    height: max_y - min_y,
+                },
+            )
+        }
+        Shape::RoundedRect { origin, size, .. } => (*origin, *size),
+    }
+}
+
+// Interpolates between the stops in sRGB, like Cairo does.
+fn gradient_color(gradient: &Gradient, t: f32, theme: &Theme) -> Color {
+    let stops = &gradient.stops;
+    let Some(first) = stops.first() else {
+        return Color::TRANSPARENT;
+    };
+    if t <= first.offset {
+        return first.color.resolve(theme);
+    }
+    for pair in stops.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        if t <= to.offset {
+            let span = to.offset - from.offset;
+            let t = if span > 0.0 {
+                (t - from.offset) / span
+            } else {
+                1.0
+            };
+            let (from, to) = (from.color.resolve(theme), to.color.resolve(theme));
+            let mix = |a: f32, b: f32| a + (b - a) * t;
+            return Color::rgba(
+                mix(from.r, to.r),
+                mix(from.g, to.g),
+                mix(from.b, to.b),
+                mix(from.a, to.a),
+            );
+        }
+    }
+    stops[stops.len() - 1].color.resolve(theme)
+}
+
+impl GraphicsContext for HeadlessContext {
+    fn save(&mut self) {
+        self.saved.push(self.state.clone());
+    }
+    fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+    }
+    fn translate(&mut self, tx: f32, ty: f32) {
+        self.state.offset.x += tx;
+        self.state.offset.y += ty;
+    }
+    fn clip_rect(&mut self, point: Point<f32>, size: Size<f32>) {
+        let shape = self.shape(Shape::rect(point, size, CornerRadius::ZERO));
+        self.state.clip.push(shape);
+    }
+    fn clip_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: f32) {
+        let shape = self.shape(Shape::rect(point, size, CornerRadius::all(radius)));
+        self.state.clip.push(shape);
+    }
+
+    fn set_color(&mut self, color: StyleColor) {
+        self.state.color = color.resolve(&self.theme);
+    }
+    fn draw_border_sides(
+        &mut self,
+        size: Size<f32>,
+        border: Rect<f32>,
+        colors: Rect<Option<StyleColor>>,
+        radius: CornerRadius,
+        style: BorderStyle,
+    ) {
+        let radius = radius.clamp(size);
+        let outer = self.shape(Shape::rect(Point::ZERO, size, radius));
+        let inner_size = Size {
+            width: size.width - border.left - border.right,
+            height: size.height - border.top - border.bottom,
+        };
+        let inner_radius = CornerRadius {
+            top_left: radius.top_left - border.top.max(border.left),
+            top_right: radius.top_right - border.top.max(border.right),
+            bottom_right: radius.bottom_right - border.bottom.max(border.right),
+            bottom_left: radius.bottom_left - border.bottom.max(border.left),
+        };
+        let inner = self.shape(Shape::rect(
+            Point {
+                x: border.left,
+                y: border.top,
+            },
+            inner_size,
+            inner_radius,
+        ));
+        let wedges = border_wedges(size, border);
+        let offset = self.state.offset;
+        // Each side and whether it runs horizontally, for dashes.
+        let sides = [
+            (border.top, colors.top, wedges.top, true),
+            (border.right, colors.right, wedges.right, false),
+            (border.bottom, colors.bottom, wedges.bottom, true),
+            (border.left, colors.left, wedges.left, false),
+        ];
+        for (width, color, wedge, horizontal) in sides {
+            let Some(color) = color.filter(|_| width > 0.0) else {
+                continue;
+            };
+            let color = color.resolve(&self.theme);
+            let wedge = self.shape(Shape::Polygons(vec![wedge.to_vec()]));
+            let length = if horizontal { size.width } else { size.height };
+            let dashes = border_dashes(length, width, style);
+            let (origin, bounds_size) = bounds(&outer);
+            self.fill(origin, bounds_size, |point| {
+                let along = if horizontal {
+                    point.x - offset.x
+                } else {
+                    point.y - offset.y
+                };
+                let dash = dashes
+                    .iter()
+                    .any(|&(start, end)| along >= start && along < end);
+                (dash && wedge.contains(point) && outer.contains(point) && !inner.contains(point))
+                    .then_some(color)
+            });
+        }
+    }
+    fn draw_rect(&mut self, point: Point<f32>, size: Size<f32>) {
+        let shape = self.shape(Shape::rect(point, size, CornerRadius::ZERO));
+        self.fill_shape(shape, self.state.color);
+    }
+    fn fill_polygons(&mut self, polygons: &[&[Point<f32>]]) {
+        let polygons = polygons.iter().map(|points| points.to_vec()).collect();
+        let shape = self.shape(Shape::Polygons(polygons));
+        self.fill_shape(shape, self.state.color);
+    }
+    fn draw_rounded_rect(&mut self, point: Point<f32>, size: Size<f32>, radius: CornerRadius) {
+        let shape = self.shape(Shape::rect(point, size, radius));
+        self.fill_shape(shape, self.state.color);
+    }
+    fn draw_gradient(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        gradient: &Gradient,
+    ) {
+        let shape = self.shape(Shape::rect(point, size, radius));
+        let (origin, _) = bounds(&shape);
+        let center = Point {
+            x: origin.x + size.width / 2.0,
+            y: origin.y + size.height / 2.0,
+        };
+        let theme = self.theme.clone();
+        // Maps a point to its position along the gradient.
+        let position: Box<dyn Fn(Point<f32>) -> f32> = match gradient.shape {
+            GradientShape::Linear { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let length = size.width * sin.abs() + size.height * cos.abs();
+                Box::new(move |p: Point<f32>| {
+                    ((p.x - center.x) * sin - (p.y - center.y) * cos) / length + 0.5
+                })
+            }
+            GradientShape::Radial => {
+                let reach = (size.width / 2.0).hypot(size.height / 2.0);
+                Box::new(move |p: Point<f32>| (p.x - center.x).hypot(p.y - center.y) / reach)
+            }
+        };
+        self.fill(origin, size, |p| {
+            shape
+                .contains(p)
+                .then(|| gradient_color(gradient, position(p), &theme))
+        });
+    }
+    fn draw_shadow(
+        &mut self,
+        point: Point<f32>,
+        size: Size<f32>,
+        radius: CornerRadius,
+        shadow: &Shadow,
+    ) {
+        let color = shadow.color.resolve(&self.theme);
+        let widget = self.shape(Shape::rect(point, size, radius));
+        // The same stack of growing outlines as the Cairo backend.
+        let steps = (shadow.blur.ceil() as usize).clamp(1, 16);
+        let alpha = 1.0 - (1.0 - color.a).powf(1.0 / steps as f32);
+        let layers: Vec<Shape> = (0..steps)
+            .filter_map(|step| {
+                let fraction = (step as f32 + 0.5) / steps as f32 - 0.5;
+                let grow = shadow.spread + shadow.blur * fraction;
+                let layer_size = size.map(|v| v + 2.0 * grow);
+                if layer_size.width <= 0.0 || layer_size.height <= 0.0 {
+                    return None;
+                }
+                let origin = Point {
+                    x: point.x + shadow.offset.x - grow,
+                    y: point.y + shadow.offset.y - grow,
+                };
+                let grown = CornerRadius {
+                    top_left: radius.top_left + grow,
+                    top_right: radius.top_right + grow,
+                    bottom_right: radius.bottom_right + grow,
+                    bottom_left: radius.bottom_left + grow,
+                };
+                Some(self.shape(Shape::rect(origin, layer_size, grown)))
+            })
+            .collect();
+        let outset = shadow.outset();
+        let origin = Point {
+            x: point.x + self.state.offset.x - outset.left,
+            y: point.y + self.state.offset.y - outset.top,
+        };
+        let extent = Size {
+            width: size.width + outset.left + outset.right,
+            height: size.height + outset.top + outset.bottom,
+        };
+        self.fill(origin, extent, |p| {
+            if widget.contains(p) {
+                return None;
+            }
+            let covered = layers.iter().filter(|layer| layer.contains(p)).count();
+            (covered > 0).then(|| color.with_alpha(1.0 - (1.0 - alpha).powi(covered as i32)))
+        });
+    }
+    fn draw_text(&mut self, _point: Point<f32>, _size: Size<f32>, _text: &TextSection) {}
+}
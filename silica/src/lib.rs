@@ -1,18 +1,42 @@
+mod animation;
+mod border;
+mod color;
+mod damage;
+mod event;
+mod executor;
 mod graphics;
+mod headless;
 pub mod model;
+mod proxy;
 pub mod signal;
+mod stylesheet;
+mod theme;
+mod timer;
+mod tooltip;
 pub mod widget;
 
 use std::{
-    cell::{Cell, Ref, RefCell},
+    cell::{Cell, OnceCell, Ref, RefCell},
     rc::{Rc, Weak},
 };
 
+use model::{BoolModel, Model};
 use taffy::{geometry::Point, prelude::*};
 
+pub use animation::{Animation, Easing, FRAME_INTERVAL};
+pub use border::{border_dashes, border_quads, border_wedges, resolve_border};
+pub use color::{linear_to_srgb, srgb_to_linear, Color, ParseColorError};
+pub use damage::*;
+pub use event::*;
+pub use executor::TaskHandle;
 pub use graphics::*;
-pub use signal::{Signal, Signals};
+pub use headless::HeadlessContext;
+pub use proxy::GuiProxy;
+pub use signal::{Connection, Signal, SignalFuture, Signals};
+pub use stylesheet::{Rule, StyleSheet, StyleSheetError};
 pub use taffy;
+pub use theme::{Theme, ThemeError};
+pub use timer::{Clock, MockClock, SystemClock, TimerHandle};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PointerState {
@@ -21,17 +45,88 @@ pub enum PointerState {
     Press,
 }
 
+/// Whether children are clipped to their parent's bounds when drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Overflow {
+    #[default]
+    Visible,
+    Clip,
+}
+
+/// Whether a widget is drawn and takes space in the layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Visibility {
+    #[default]
+    Visible,
+    /// Not drawn and not hit, but still occupies its space in the layout.
+    Hidden,
+    /// Not drawn and takes no space, like taffy's [`Display::None`].
+    Collapsed,
+}
+
 pub trait WidgetObject: Sized {
+    /// The name that selects the widget in a [`StyleSheet`].
+    fn type_name() -> &'static str {
+        "Widget"
+    }
     fn draw(data: &WidgetData<Self>, context: &mut dyn GraphicsContext, size: Size<f32>);
     fn set_pointer_state(_data: Rc<WidgetData<Self>>, _state: PointerState) {}
+    /// Whether the [`checked`](VisualStyle::checked) colors apply.
+    fn is_checked(_data: &WidgetData<Self>) -> bool {
+        false
+    }
+    fn can_focus(_data: &WidgetData<Self>) -> bool {
+        false
+    }
+    /// Draws on top of the widget's children.
+    fn draw_overlay(
+        _data: &WidgetData<Self>,
+        _context: &mut dyn GraphicsContext,
+        _size: Size<f32>,
+    ) {
+    }
+    /// Offset subtracted from the position of the widget's children, for scrolling content.
+    fn scroll_offset(_data: &WidgetData<Self>) -> Point<f32> {
+        Point::ZERO
+    }
+    /// Whether `point`, relative to the widget, can hit its children.
+    fn hit_children(_data: &WidgetData<Self>, _point: Point<f32>) -> bool {
+        true
+    }
 }
 
 pub trait WidgetDataUntyped {
     fn node(&self) -> Node;
+    fn type_name(&self) -> &'static str;
+    fn id(&self) -> Option<String>;
+    fn has_class(&self, class: &str) -> bool;
     fn draw(&self, context: &mut dyn GraphicsContext, size: Size<f32>);
-    fn children(&self) -> Ref<Vec<Widget>>;
-    fn visual(&self) -> Ref<Option<VisualStyle>>;
+    fn draw_overlay(&self, context: &mut dyn GraphicsContext, size: Size<f32>);
+    fn scroll_offset(&self) -> Point<f32>;
+    fn hit_children(&self, point: Point<f32>) -> bool;
+    fn parent(&self) -> Option<Widget>;
+    fn set_parent(&self, parent: Option<WidgetWeak>);
+    fn children(&self) -> Ref<'_, Vec<Widget>>;
+    fn remove_child(&self, child_node: Node) -> bool;
+    fn take_child(&self, child_node: Node) -> Option<Widget>;
+    fn events(&self) -> &Signals<Widget>;
+    fn visual(&self) -> Ref<'_, Option<VisualStyle>>;
+    fn visual_colors(&self) -> Option<VisualColors>;
+    fn overflow(&self) -> Overflow;
     fn can_highlight(&self) -> bool;
+    fn can_focus(&self) -> bool;
+    fn is_enabled(&self) -> bool;
+    fn set_enabled(&self, enabled: bool);
+    fn visibility(&self) -> Visibility;
+    fn is_visible(&self) -> bool;
+    fn set_visibility(&self, visibility: Visibility);
+    fn tab_index(&self) -> Option<u32>;
+    fn tooltip(&self) -> Option<String>;
+    fn set_visual(&self, visual: Option<VisualStyle>);
+    fn layout(&self) -> Style;
+    fn set_layout(&self, layout: Style);
+    fn update_style(&self);
+    fn queue_draw(&self);
     fn set_pointer_state(self: Rc<Self>, state: PointerState);
 }
 
@@ -41,15 +136,37 @@ pub type WidgetWeak = Weak<dyn WidgetDataUntyped>;
 pub struct WidgetData<T> {
     gui: Rc<Gui>,
     node: Node,
+    // The layout and visual set on the widget, which the style sheet rules are applied to.
+    layout: RefCell<Style>,
+    base_visual: RefCell<Option<VisualStyle>>,
+    // The style sheet rules that match the widget, from the lowest precedence to the highest.
+    rules: RefCell<Vec<Rule>>,
+    // The visual drawn, with the rules applied.
     visual: RefCell<Option<VisualStyle>>,
+    overflow: Cell<Overflow>,
     can_highlight: bool,
+    enabled: Cell<bool>,
+    visibility: Cell<Visibility>,
+    tab_index: Cell<Option<u32>>,
+    tooltip: RefCell<Option<String>>,
+    id: RefCell<Option<String>>,
+    classes: RefCell<Vec<String>>,
+    pointer_state: Cell<PointerState>,
+    transition: RefCell<Option<animation::Transition>>,
+    visual_animation: RefCell<Option<Animation>>,
+    layout_animation: RefCell<Option<Animation>>,
+    events: Signals<Widget>,
     object: T,
 
-    // parent: RefCell<Option<WidgetWeak>>,
+    this: WidgetWeak,
+    parent: RefCell<Option<WidgetWeak>>,
     children: RefCell<Vec<Widget>>,
 }
 
-impl<T> WidgetData<T> {
+impl<T> WidgetData<T>
+where
+    T: WidgetObject + 'static,
+{
     pub fn new(gui: Rc<Gui>, draw: bool, object: T) -> Rc<Self> {
         let visual = if draw {
             Some(VisualStyle::default())
@@ -64,25 +181,77 @@ impl<T> WidgetData<T> {
         visual: Option<VisualStyle>,
         object: T,
     ) -> Rc<Self> {
-        let node = gui.layout.borrow_mut().new_leaf(style).unwrap();
+        let node = gui.layout.borrow_mut().new_leaf(style.clone()).unwrap();
         let can_highlight = visual
             .as_ref()
             .map(|vis| vis.background.is_some())
             .unwrap_or(false);
-        Rc::new(WidgetData {
+        Rc::new_cyclic(|this: &Weak<Self>| WidgetData {
             gui,
             node,
+            layout: RefCell::new(style),
+            base_visual: RefCell::new(visual.clone()),
+            rules: RefCell::default(),
             visual: RefCell::new(visual),
+            overflow: Cell::new(Overflow::Visible),
             can_highlight,
+            enabled: Cell::new(true),
+            visibility: Cell::new(Visibility::Visible),
+            tab_index: Cell::new(None),
+            tooltip: RefCell::new(None),
+            id: RefCell::new(None),
+            classes: RefCell::default(),
+            pointer_state: Cell::new(PointerState::None),
+            transition: RefCell::new(None),
+            visual_animation: RefCell::new(None),
+            layout_animation: RefCell::new(None),
+            events: Signals::new(),
             object,
+            this: this.clone(),
+            parent: RefCell::default(),
             children: RefCell::default(),
         })
     }
+}
+
+impl<T: WidgetObject> WidgetData<T> {
+    /// The state that selects the colors from the widget's [`VisualStyle`].
+    pub fn visual_state(&self) -> VisualState {
+        let pointer = self.pointer_state.get();
+        VisualState {
+            hovered: pointer != PointerState::None,
+            pressed: pointer == PointerState::Press,
+            focused: self.has_focus(),
+            disabled: !self.is_enabled(),
+            checked: T::is_checked(self),
+        }
+    }
+    /// The colors the widget is drawn with, including a running state transition.
+    pub fn visual_colors(&self) -> Option<VisualColors> {
+        if let Some(colors) = self.transition.borrow().as_ref().and_then(|t| t.colors()) {
+            return Some(colors);
+        }
+        let visual = self.visual.borrow();
+        visual
+            .as_ref()
+            .map(|visual| visual.colors(self.visual_state()))
+    }
+}
 
+impl<T> WidgetData<T> {
     pub fn gui(&self) -> Rc<Gui> {
         self.gui.clone()
     }
+    pub fn widget(&self) -> Widget {
+        self.this.upgrade().unwrap()
+    }
+    /// The layout set with [`set_layout`](Self::set_layout), without the style sheet.
     pub fn layout(&self) -> Style {
+        self.layout.borrow().clone()
+    }
+    /// The layout the widget is laid out with: its own layout with the matching style sheet
+    /// rules applied.
+    pub fn computed_layout(&self) -> Style {
         self.gui.layout.borrow().style(self.node).unwrap().clone()
     }
     pub fn size(&self) -> Size<f32> {
@@ -91,54 +260,409 @@ impl<T> WidgetData<T> {
         layout.size
     }
 
+    /// Sets the widget's own layout. Matching style sheet rules still apply on top of it.
     pub fn set_layout(&self, layout: Style) {
+        *self.layout.borrow_mut() = layout;
+        self.apply_layout();
+    }
+    fn apply_layout(&self) {
+        let mut layout = self.layout();
+        for rule in self.rules.borrow().iter() {
+            rule.apply_layout(&mut layout);
+        }
+        if self.visibility.get() == Visibility::Collapsed {
+            layout.display = Display::None;
+        }
+        let mut tree = self.gui.layout.borrow_mut();
+        if tree.style(self.node).unwrap() != &layout {
+            tree.set_style(self.node, layout).unwrap();
+            self.gui.mark_needs_layout();
+        }
+    }
+    fn apply_visual(&self) {
+        let mut visual = self.base_visual.borrow().clone();
+        for rule in self.rules.borrow().iter() {
+            rule.apply_visual(&mut visual);
+        }
+        if *self.visual.borrow() != visual {
+            self.cancel_transition();
+            // Repaint the old area too, in case a shadow shrank.
+            self.queue_draw();
+            *self.visual.borrow_mut() = visual;
+            self.queue_draw();
+        }
+    }
+    pub fn window_position(&self) -> Point<f32> {
+        self.gui.window_position(&self.widget())
+    }
+    pub fn set_overflow(&self, overflow: Overflow) {
+        self.overflow.set(overflow);
+        self.queue_draw();
+    }
+    /// Schedules a layout pass, for changes to what the widget measures.
+    pub fn queue_layout(&self) {
+        let _ = self.gui.layout.borrow_mut().mark_dirty(self.node);
+        self.gui.mark_needs_layout();
+    }
+    /// Schedules a repaint of the area covered by this widget.
+    pub fn queue_draw(&self) {
+        if let Some(this) = self.this.upgrade() {
+            self.gui.damage_widget(&this);
+        }
+    }
+    /// Places this widget in the explicit tab order. Widgets with a tab index are visited first,
+    /// in ascending order, followed by the remaining focusable widgets in tree order.
+    pub fn set_tab_index(&self, tab_index: Option<u32>) {
+        self.tab_index.set(tab_index);
+    }
+    /// Sets the text shown in a popup when the pointer rests over this widget.
+    pub fn set_tooltip(&self, tooltip: Option<String>) {
+        *self.tooltip.borrow_mut() = tooltip;
+    }
+    pub fn tooltip(&self) -> Option<String> {
+        self.tooltip.borrow().clone()
+    }
+    /// Sets the name that selects this widget with `#id` in a [`StyleSheet`].
+    pub fn set_id(&self, id: Option<String>) {
+        *self.id.borrow_mut() = id;
+        self.restyle();
+    }
+    pub fn id(&self) -> Option<String> {
+        self.id.borrow().clone()
+    }
+    /// Adds a class that selects this widget with `.class` in a [`StyleSheet`].
+    pub fn add_class(&self, class: &str) {
+        if !self.has_class(class) {
+            self.classes.borrow_mut().push(class.to_string());
+            self.restyle();
+        }
+    }
+    /// Removes a class and reapplies the style sheet.
+    pub fn remove_class(&self, class: &str) {
+        let removed = {
+            let mut classes = self.classes.borrow_mut();
+            let len = classes.len();
+            classes.retain(|c| c != class);
+            classes.len() != len
+        };
+        if removed {
+            self.restyle();
+        }
+    }
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.borrow().iter().any(|c| c == class)
+    }
+    pub fn classes(&self) -> Vec<String> {
+        self.classes.borrow().clone()
+    }
+    fn restyle(&self) {
+        if let Some(this) = self.this.upgrade() {
+            self.gui.restyle(&this);
+        }
+    }
+    pub fn has_focus(&self) -> bool {
+        self.gui
+            .focused()
+            .map(|widget| widget.node() == self.node)
+            .unwrap_or(false)
+    }
+    pub fn grab_pointer(&self) {
+        self.gui.grab_pointer(self.widget());
+    }
+    pub fn release_pointer(&self) {
+        if self.has_pointer_grab() {
+            self.gui.release_pointer();
+        }
+    }
+    pub fn has_pointer_grab(&self) -> bool {
+        self.gui
+            .pointer_grab()
+            .map(|widget| widget.node() == self.node)
+            .unwrap_or(false)
+    }
+    /// Enables or disables this widget and its subtree. Disabled widgets don't highlight, take
+    /// focus or activate, and are drawn dimmed.
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.replace(enabled) == enabled {
+            return;
+        }
+        self.queue_draw();
+        if let Some(this) = self.this.upgrade() {
+            self.gui.update_interactive(&this);
+        }
+    }
+    /// Whether this widget and all its ancestors are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+            && self
+                .parent_widget()
+                .map(|parent| parent.is_enabled())
+                .unwrap_or(true)
+    }
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.get()
+    }
+    /// Hidden and collapsed widgets, and their subtrees, are not drawn, hit or focused.
+    pub fn set_visibility(&self, visibility: Visibility) {
+        let old_visibility = self.visibility.replace(visibility);
+        if old_visibility == visibility {
+            return;
+        }
+        if old_visibility == Visibility::Collapsed || visibility == Visibility::Collapsed {
+            // `set_layout` applies `Display::None` according to the new visibility.
+            self.set_layout(self.layout());
+        } else {
+            self.queue_draw();
+        }
+        if let Some(this) = self.this.upgrade() {
+            self.gui.update_interactive(&this);
+        }
+    }
+    /// Shows the widget, or collapses it so it takes no space.
+    pub fn set_visible(&self, visible: bool) {
+        self.set_visibility(if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Collapsed
+        });
+    }
+    /// Whether this widget and all its ancestors are visible.
+    pub fn is_visible(&self) -> bool {
+        self.visibility.get() == Visibility::Visible
+            && self
+                .parent_widget()
+                .map(|parent| parent.is_visible())
+                .unwrap_or(true)
+    }
+    /// Makes the widget visible while `model` is `true`, and collapsed otherwise.
+    pub fn bind_visible(&self, model: &Rc<BoolModel>) -> Connection {
+        let this = self.this.clone();
+        model.connect_change(move |model, signal::Change| {
+            if let Some(widget) = this.upgrade() {
+                widget.set_visibility(if model.get() {
+                    Visibility::Visible
+                } else {
+                    Visibility::Collapsed
+                });
+            }
+        })
+    }
+    /// Makes the enabled state follow `model`.
+    pub fn bind_enabled(&self, model: &Rc<BoolModel>) -> Connection {
+        let this = self.this.clone();
+        model.connect_change(move |model, signal::Change| {
+            if let Some(widget) = this.upgrade() {
+                widget.set_enabled(model.get());
+            }
+        })
+    }
+    pub fn grab_focus(&self) -> bool {
+        self.gui.set_focus(Some(self.widget()))
+    }
+    /// Sets the widget's own visual style. Matching style sheet rules still apply on top of it.
+    pub fn set_visual(&self, visual: Option<VisualStyle>) {
+        self.cancel_transition();
+        self.queue_draw();
+        *self.base_visual.borrow_mut() = visual;
+        self.apply_visual();
+    }
+    /// Matches the widget against the installed style sheet again and reapplies it.
+    pub(crate) fn update_style(&self) {
+        let rules = match (self.gui.style_sheet(), self.this.upgrade()) {
+            (Some(sheet), Some(this)) => sheet.matching_rules(&this).into_iter().cloned().collect(),
+            _ => Vec::new(),
+        };
+        *self.rules.borrow_mut() = rules;
+        self.apply_layout();
+        self.apply_visual();
+    }
+    fn parent_widget(&self) -> Option<Widget> {
+        self.parent.borrow().as_ref().and_then(Weak::upgrade)
+    }
+    pub fn ancestors(&self) -> impl Iterator<Item = Widget> {
+        std::iter::successors(self.parent_widget(), |widget| widget.parent())
+    }
+    pub fn index_in_parent(&self) -> Option<usize> {
+        let parent = self.parent_widget()?;
+        let index = parent
+            .children()
+            .iter()
+            .position(|child| child.node() == self.node);
+        index
+    }
+    pub fn remove_from_parent(&self) -> bool {
+        self.parent_widget()
+            .map(|parent| parent.remove_child(self.node))
+            .unwrap_or(false)
+    }
+
+    fn sync_children(&self) {
+        let nodes: Vec<Node> = self.children.borrow().iter().map(|v| v.node()).collect();
         self.gui
             .layout
             .borrow_mut()
-            .set_style(self.node, layout)
+            .set_children(self.node, &nodes)
             .unwrap();
-        self.gui.mark_dirty();
+        self.gui.mark_needs_layout();
     }
-    pub fn set_visual(&self, visual: Option<VisualStyle>) {
-        *self.visual.borrow_mut() = visual;
-        self.gui.mark_dirty();
+    // Detaches `child` to move it elsewhere, keeping its focus and grab.
+    fn detach(child: &Widget) {
+        if let Some(parent) = child.parent() {
+            parent.take_child(child.node());
+        }
     }
-    pub fn add_child<W>(&self, child: W)
+    // A widget cannot contain itself, directly or through its children.
+    fn can_adopt(&self, child: &Widget) -> bool {
+        child.node() != self.node && self.ancestors().all(|widget| widget.node() != child.node())
+    }
+    /// Appends `child`, detaching it from its current parent first. Returns `false`, changing
+    /// nothing, if `child` is this widget or one of its ancestors.
+    pub fn add_child<W>(&self, child: W) -> bool
     where
         W: Into<Widget>,
     {
         let child = child.into();
-        self.gui
-            .layout
-            .borrow_mut()
-            .add_child(self.node, child.node())
-            .unwrap();
-        self.children.borrow_mut().push(child);
-        self.gui.mark_dirty();
+        if !self.can_adopt(&child) {
+            return false;
+        }
+        Self::detach(&child);
+        child.set_parent(Some(self.this.clone()));
+        self.children.borrow_mut().push(child.clone());
+        self.sync_children();
+        self.gui.restyle(&child);
+        true
     }
-    pub fn remove_child(&self, child_node: Node) -> bool {
-        let mut children = self.children.borrow_mut();
-        if let Some(index) = children.iter().position(|v| v.node() == child_node) {
-            self.gui
-                .layout
-                .borrow_mut()
-                .remove_child(self.node, child_node)
-                .unwrap();
-            children.remove(index);
-            self.gui.mark_dirty();
-            true
-        } else {
-            false
+    /// Inserts `child` at `index`, detaching it from its current parent first. If `child` is
+    /// already a child of this widget, `index` refers to the position after it was removed.
+    /// Returns `false`, changing nothing, if `index` is out of range or `child` is this widget or
+    /// one of its ancestors.
+    pub fn insert_child<W>(&self, index: usize, child: W) -> bool
+    where
+        W: Into<Widget>,
+    {
+        let child = child.into();
+        let len = self.children.borrow().len();
+        let len = match self.index_of_child(child.node()) {
+            Some(_) => len - 1,
+            None => len,
+        };
+        if index > len || !self.can_adopt(&child) {
+            return false;
         }
+        Self::detach(&child);
+        child.set_parent(Some(self.this.clone()));
+        self.children.borrow_mut().insert(index, child.clone());
+        self.sync_children();
+        self.gui.restyle(&child);
+        true
+    }
+    /// Moves the child at `from` to `to`. Returns `false` if either index is out of range.
+    pub fn move_child(&self, from: usize, to: usize) -> bool {
+        {
+            let mut children = self.children.borrow_mut();
+            if from >= children.len() || to >= children.len() {
+                return false;
+            }
+            let child = children.remove(from);
+            children.insert(to, child);
+        }
+        self.sync_children();
+        true
+    }
+    /// Replaces the child with node `old_node` by `child` and returns the old child, or `None` if
+    /// `old_node` is not a child of this widget or `child` is this widget or one of its ancestors.
+    pub fn replace_child<W>(&self, old_node: Node, child: W) -> Option<Widget>
+    where
+        W: Into<Widget>,
+    {
+        let child = child.into();
+        if child.node() == old_node || !self.can_adopt(&child) {
+            return None;
+        }
+        // Check before detaching so a missing `old_node` leaves `child` where it was.
+        self.index_of_child(old_node)?;
+        Self::detach(&child);
+        let index = self.index_of_child(old_node)?;
+        child.set_parent(Some(self.this.clone()));
+        let old_child = std::mem::replace(&mut self.children.borrow_mut()[index], child.clone());
+        old_child.set_parent(None);
+        self.sync_children();
+        self.gui.restyle(&old_child);
+        self.gui.restyle(&child);
+        self.gui.update_interactive(&old_child);
+        Some(old_child)
+    }
+    fn index_of_child(&self, child_node: Node) -> Option<usize> {
+        self.children
+            .borrow()
+            .iter()
+            .position(|v| v.node() == child_node)
+    }
+    fn take_child(&self, child_node: Node) -> Option<Widget> {
+        let index = self.index_of_child(child_node)?;
+        let child = self.children.borrow_mut().remove(index);
+        child.set_parent(None);
+        self.sync_children();
+        Some(child)
+    }
+    /// Removes the child with node `child_node`. Focus, pointer grab and highlight held inside its
+    /// subtree are dropped.
+    pub fn remove_child(&self, child_node: Node) -> bool {
+        let Some(child) = self.take_child(child_node) else {
+            return false;
+        };
+        self.gui.restyle(&child);
+        self.gui.update_interactive(&child);
+        true
     }
     pub fn remove_children(&self) {
-        self.gui
-            .layout
-            .borrow_mut()
-            .set_children(self.node, &[])
-            .unwrap();
-        self.children.borrow_mut().clear();
-        self.gui.mark_dirty();
+        let children = std::mem::take(&mut *self.children.borrow_mut());
+        for child in &children {
+            child.set_parent(None);
+        }
+        self.sync_children();
+        for child in &children {
+            self.gui.restyle(child);
+            self.gui.update_interactive(child);
+        }
+    }
+
+    /// Connects a handler for events of type `S` that reach this widget in the
+    /// [`Target`](EventPhase::Target) or [`Bubble`](EventPhase::Bubble) phase.
+    pub fn connect_event<S, F>(&self, mut handler: F) -> Connection
+    where
+        S: Signal,
+        F: FnMut(Widget, Event<S>) + 'static,
+    {
+        self.events.connect(move |widget, event: Event<S>| {
+            if event.phase != EventPhase::Capture {
+                handler(widget, event);
+            }
+        })
+    }
+    /// Connects a handler that is called when this widget, or a descendant without its own
+    /// context menu handler, is clicked with the secondary button.
+    pub fn connect_context_menu<F>(&self, mut handler: F) -> Connection
+    where
+        F: FnMut(Widget, signal::ContextMenu) + 'static,
+    {
+        self.connect_event(move |widget, event: Event<signal::ContextMenu>| {
+            event.stop_propagation();
+            handler(widget, event.signal);
+        })
+    }
+    /// Connects a handler for events of type `S` that reach this widget in the
+    /// [`Capture`](EventPhase::Capture) or [`Target`](EventPhase::Target) phase.
+    pub fn connect_capture<S, F>(&self, mut handler: F) -> Connection
+    where
+        S: Signal,
+        F: FnMut(Widget, Event<S>) + 'static,
+    {
+        self.events.connect(move |widget, event: Event<S>| {
+            if event.phase != EventPhase::Bubble {
+                handler(widget, event);
+            }
+        })
     }
 }
 
@@ -155,77 +679,259 @@ where
     fn node(&self) -> Node {
         self.node
     }
+    fn type_name(&self) -> &'static str {
+        T::type_name()
+    }
+    fn id(&self) -> Option<String> {
+        WidgetData::id(self)
+    }
+    fn has_class(&self, class: &str) -> bool {
+        WidgetData::has_class(self, class)
+    }
     fn draw(&self, context: &mut dyn GraphicsContext, size: Size<f32>) {
         T::draw(self, context, size);
     }
-    fn children(&self) -> Ref<Vec<Widget>> {
+    fn draw_overlay(&self, context: &mut dyn GraphicsContext, size: Size<f32>) {
+        T::draw_overlay(self, context, size);
+    }
+    fn scroll_offset(&self) -> Point<f32> {
+        T::scroll_offset(self)
+    }
+    fn hit_children(&self, point: Point<f32>) -> bool {
+        T::hit_children(self, point)
+    }
+    fn parent(&self) -> Option<Widget> {
+        self.parent_widget()
+    }
+    fn set_parent(&self, parent: Option<WidgetWeak>) {
+        *self.parent.borrow_mut() = parent;
+    }
+    fn children(&self) -> Ref<'_, Vec<Widget>> {
         self.children.borrow()
     }
-    fn visual(&self) -> Ref<Option<VisualStyle>> {
+    fn remove_child(&self, child_node: Node) -> bool {
+        WidgetData::remove_child(self, child_node)
+    }
+    fn take_child(&self, child_node: Node) -> Option<Widget> {
+        WidgetData::take_child(self, child_node)
+    }
+    fn events(&self) -> &Signals<Widget> {
+        &self.events
+    }
+    fn visual(&self) -> Ref<'_, Option<VisualStyle>> {
         self.visual.borrow()
     }
+    fn visual_colors(&self) -> Option<VisualColors> {
+        WidgetData::visual_colors(self)
+    }
+    fn overflow(&self) -> Overflow {
+        self.overflow.get()
+    }
     fn can_highlight(&self) -> bool {
         self.can_highlight
     }
+    fn can_focus(&self) -> bool {
+        T::can_focus(self) && self.is_enabled() && self.is_visible()
+    }
+    fn is_enabled(&self) -> bool {
+        WidgetData::is_enabled(self)
+    }
+    fn set_enabled(&self, enabled: bool) {
+        WidgetData::set_enabled(self, enabled)
+    }
+    fn visibility(&self) -> Visibility {
+        self.visibility.get()
+    }
+    fn is_visible(&self) -> bool {
+        WidgetData::is_visible(self)
+    }
+    fn set_visibility(&self, visibility: Visibility) {
+        WidgetData::set_visibility(self, visibility)
+    }
+    fn tab_index(&self) -> Option<u32> {
+        self.tab_index.get()
+    }
+    fn tooltip(&self) -> Option<String> {
+        self.tooltip.borrow().clone()
+    }
+    fn set_visual(&self, visual: Option<VisualStyle>) {
+        WidgetData::set_visual(self, visual)
+    }
+    fn layout(&self) -> Style {
+        WidgetData::layout(self)
+    }
+    fn set_layout(&self, layout: Style) {
+        WidgetData::set_layout(self, layout)
+    }
+    fn update_style(&self) {
+        WidgetData::update_style(self)
+    }
+    fn queue_draw(&self) {
+        WidgetData::queue_draw(self)
+    }
     fn set_pointer_state(self: Rc<Self>, state: PointerState) {
-        T::set_pointer_state(self, state);
+        let from = self.visual_colors();
+        self.pointer_state.set(state);
+        T::set_pointer_state(self.clone(), state);
+        self.transition_visual(from, state);
     }
 }
 
+type PointerStateChanges = Vec<(Widget, PointerState)>;
+
+fn same_widget(a: Option<&Widget>, b: Option<&Widget>) -> bool {
+    a.map(|w| w.node()) == b.map(|w| w.node())
+}
+
+// Methods return the pointer state changes instead of applying them, so that widget callbacks
+// never run while the state is borrowed.
 #[derive(Default)]
 pub struct GuiState {
     highlight: Option<Widget>,
+    // The widget under the pointer that has a tooltip.
+    hover: Option<Widget>,
+    focus: Option<Widget>,
+    pointer: Option<Point<f32>>,
     pointer_press: bool,
+    // While the pointer is pressed, `highlight` stays on the pressed widget and this tracks
+    // whether the pointer is still inside it.
+    press_inside: bool,
+    grab: Option<Widget>,
+    explicit_grab: bool,
 }
 
 impl GuiState {
-    pub fn on_pointer_move(&mut self, gui: &Gui, root: &Widget, x: f32, y: f32) {
-        let highlight = gui.hit_highlightable_widget(x, y, root);
-        if highlight.as_ref().map(|w| w.node()) != self.highlight.as_ref().map(|w| w.node()) {
+    pub fn on_pointer_move(
+        &mut self,
+        gui: &Gui,
+        root: &Widget,
+        x: f32,
+        y: f32,
+    ) -> PointerStateChanges {
+        let mut changes = Vec::new();
+        self.pointer = Some(Point { x, y });
+        self.hover = gui.hit_widget(x, y, root, &|widget| widget.tooltip().is_some());
+        let highlight = gui.hit_widget(x, y, root, &|widget| {
+            widget.can_highlight() && widget.is_enabled()
+        });
+        if self.pointer_press {
+            if let Some(widget) = self.highlight.clone() {
+                let inside = same_widget(highlight.as_ref(), Some(&widget));
+                if inside != self.press_inside {
+                    self.press_inside = inside;
+                    let state = if inside {
+                        PointerState::Press
+                    } else {
+                        PointerState::None
+                    };
+                    changes.push((widget, state));
+                }
+            }
+        } else if !same_widget(highlight.as_ref(), self.highlight.as_ref()) {
             if let Some(widget) = self.highlight.clone() {
-                widget.set_pointer_state(PointerState::None);
+                changes.push((widget, PointerState::None));
             }
             if let Some(widget) = highlight.clone() {
-                widget.set_pointer_state(if self.pointer_press {
-                    PointerState::Press
-                } else {
-                    PointerState::Over
-                });
+                changes.push((widget, PointerState::Over));
             }
             self.highlight = highlight;
         }
+        changes
     }
-    pub fn on_pointer_button(&mut self, pointer_press: bool) {
-        if let Some(widget) = self.highlight.clone() {
-            widget.set_pointer_state(if pointer_press {
-                PointerState::Press
-            } else {
-                PointerState::Over
-            });
-        }
+    pub fn on_pointer_button(
+        &mut self,
+        gui: &Gui,
+        root: &Widget,
+        pointer_press: bool,
+    ) -> PointerStateChanges {
+        let mut changes = Vec::new();
         self.pointer_press = pointer_press;
+        if pointer_press {
+            self.press_inside = true;
+            if let Some(widget) = self.highlight.clone() {
+                changes.push((widget, PointerState::Press));
+            }
+        } else {
+            if let Some(widget) = self.highlight.clone() {
+                if self.press_inside {
+                    changes.push((widget, PointerState::Over));
+                } else {
+                    self.highlight = None;
+                }
+            }
+            if let Some(pointer) = self.pointer {
+                changes.extend(self.on_pointer_move(gui, root, pointer.x, pointer.y));
+            }
+        }
+        changes
+    }
+    /// The widget that pointer events are sent to: the grabbing widget if there is one,
+    /// otherwise the topmost widget under the pointer.
+    fn pointer_target(&self, gui: &Gui, root: &Widget) -> Option<Widget> {
+        self.grab.clone().or_else(|| {
+            self.pointer
+                .and_then(|pointer| gui.hit_widget(pointer.x, pointer.y, root, &|_| true))
+        })
     }
 }
 
 pub struct Gui {
-    dirty: Cell<bool>,
+    damage: RefCell<DamageRegion>,
+    full_damage: Cell<bool>,
+    needs_layout: Cell<bool>,
+    layout_count: Cell<u64>,
+    window_size: Cell<Option<Size<f32>>>,
     layout: RefCell<Taffy>,
     state: RefCell<GuiState>,
+    root: OnceCell<WidgetWeak>,
     signals: Signals<Rc<Self>>,
+    key_handlers: Signals<Rc<Self>>,
+    user_events: Signals<Rc<Self>>,
+    proxy: std::sync::Arc<proxy::ProxyShared>,
+    tasks: Rc<RefCell<executor::Tasks>>,
+    animations: RefCell<animation::Animations>,
+    timers: Rc<RefCell<timer::Timers>>,
+    clock: RefCell<Rc<dyn Clock>>,
+    theme: RefCell<Rc<Theme>>,
+    style_sheet: RefCell<Option<Rc<StyleSheet>>>,
+    tooltip: RefCell<tooltip::TooltipState>,
 }
 
 impl Gui {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> widget::Container {
         let gui = Rc::new(Gui {
-            dirty: Cell::new(false),
+            damage: RefCell::default(),
+            full_damage: Cell::new(false),
+            needs_layout: Cell::new(true),
+            layout_count: Cell::new(0),
+            window_size: Cell::new(None),
             layout: RefCell::new(Taffy::new()),
             state: RefCell::default(),
+            root: OnceCell::new(),
             signals: Signals::new(),
+            key_handlers: Signals::new(),
+            user_events: Signals::new(),
+            proxy: Default::default(),
+            tasks: Rc::default(),
+            animations: RefCell::default(),
+            timers: Rc::default(),
+            clock: RefCell::new(Rc::new(SystemClock)),
+            theme: RefCell::default(),
+            style_sheet: RefCell::default(),
+            tooltip: RefCell::default(),
         });
         let root = widget::Container::new(gui.clone());
+        let _ = gui.root.set(Rc::downgrade(&root.widget()));
         gui.signals.connect({
             let root = root.clone();
             move |gui, signal::Layout(available_space)| {
+                if gui.window_size.replace(available_space) != available_space {
+                    gui.mark_needs_layout();
+                }
+                if !gui.needs_layout.replace(false) {
+                    return;
+                }
                 let available_space = available_space
                     .map(|size| size.map(AvailableSpace::Definite))
                     .unwrap_or(Size::MAX_CONTENT);
@@ -233,33 +939,347 @@ impl Gui {
                     .borrow_mut()
                     .compute_layout(root.node(), available_space)
                     .unwrap();
+                gui.layout_count.set(gui.layout_count.get() + 1);
+            }
+        });
+        gui.signals.connect({
+            let root: Widget = root.clone().into();
+            move |gui, motion: signal::PointerMotion| {
+                let changes = gui
+                    .state
+                    .borrow_mut()
+                    .on_pointer_move(&gui, &root, motion.x, motion.y);
+                Self::apply_pointer_states(changes);
+                gui.update_tooltip();
+                let target = gui.state.borrow().pointer_target(&gui, &root);
+                if let Some(target) = target {
+                    gui.dispatch_event(&target, motion);
+                }
+            }
+        });
+        gui.signals.connect({
+            let root: Widget = root.clone().into();
+            move |gui, button: signal::PointerButton| {
+                if let signal::PointerButton::Primary(true)
+                | signal::PointerButton::Middle(true)
+                | signal::PointerButton::Secondary(true) = button
+                {
+                    gui.suppress_tooltip();
+                }
+                let target = gui.state.borrow().pointer_target(&gui, &root);
+                if let signal::PointerButton::Primary(state) = button {
+                    if state {
+                        let focus = target.clone().and_then(|target| {
+                            std::iter::successors(Some(target), |widget| widget.parent())
+                                .find(|widget| widget.can_focus())
+                        });
+                        if focus.is_some() {
+                            gui.set_focus(focus);
+                        }
+                        gui.state.borrow_mut().grab = target.clone();
+                    }
+                    let changes = gui.state.borrow_mut().on_pointer_button(&gui, &root, state);
+                    Self::apply_pointer_states(changes);
+                }
+                if let Some(target) = target {
+                    let stopped = gui.dispatch_event(&target, button);
+                    let pointer = gui.state.borrow().pointer;
+                    if let (false, signal::PointerButton::Secondary(true), Some(pointer)) =
+                        (stopped, button, pointer)
+                    {
+                        gui.dispatch_event(
+                            &target,
+                            signal::ContextMenu {
+                                x: pointer.x,
+                                y: pointer.y,
+                            },
+                        );
+                    }
+                }
+                if let signal::PointerButton::Primary(false) = button {
+                    let mut state = gui.state.borrow_mut();
+                    if !state.explicit_grab {
+                        state.grab = None;
+                    }
+                }
             }
         });
         gui.signals.connect({
-            let root = root.clone().into();
-            move |gui, signal::PointerMotion { x, y }| {
-                gui.state.borrow_mut().on_pointer_move(&gui, &root, x, y);
+            let root: Widget = root.clone().into();
+            move |gui, scroll: signal::Scroll| {
+                let target = gui.state.borrow().pointer_target(&gui, &root);
+                if let Some(target) = target {
+                    gui.dispatch_event(&target, scroll);
+                }
             }
         });
-        gui.signals.connect(|gui, button: signal::PointerButton| {
-            if let signal::PointerButton::Primary(state) = button {
-                gui.state.borrow_mut().on_pointer_button(state);
+        gui.signals.connect(|gui, key: signal::KeyPress| {
+            if gui.route_key(key.clone()) && key.key == signal::Key::Tab {
+                if key.modifiers.shift {
+                    gui.focus_prev();
+                } else {
+                    gui.focus_next();
+                }
             }
         });
+        gui.signals.connect(|gui, key: signal::KeyRelease| {
+            gui.route_key(key);
+        });
         root
     }
 
-    fn mark_dirty(&self) {
-        self.dirty.set(true);
+    fn apply_pointer_states(changes: PointerStateChanges) {
+        for (widget, state) in changes {
+            widget.set_pointer_state(state);
+        }
+    }
+    // Drops focus, grab and highlight from a subtree that was disabled, hidden or removed, or
+    // lets the pointer highlight a subtree that was enabled or shown.
+    fn update_interactive(&self, widget: &Widget) {
+        let in_subtree = |other: &Option<Widget>| {
+            other.as_ref().is_some_and(|other| {
+                std::iter::successors(Some(other.clone()), |widget| widget.parent())
+                    .any(|ancestor| ancestor.node() == widget.node())
+            })
+        };
+        let root = self.root.get().and_then(Weak::upgrade);
+        let attached = std::iter::successors(Some(widget.clone()), |widget| widget.parent())
+            .any(|ancestor| Some(ancestor.node()) == root.as_ref().map(|root| root.node()));
+        let interactive = attached && widget.is_enabled() && widget.is_visible();
+        if !interactive && in_subtree(&self.focused()) {
+            self.set_focus(None);
+        }
+        let Some(root) = root else {
+            return;
+        };
+        let changes = {
+            let mut state = self.state.borrow_mut();
+            let mut changes = Vec::new();
+            if !interactive {
+                if in_subtree(&state.grab) {
+                    state.grab = None;
+                    state.explicit_grab = false;
+                }
+                if in_subtree(&state.highlight) {
+                    changes.extend(state.highlight.take().map(|w| (w, PointerState::None)));
+                    state.press_inside = false;
+                }
+            }
+            if let (false, Some(pointer)) = (state.pointer_press, state.pointer) {
+                changes.extend(state.on_pointer_move(self, &root, pointer.x, pointer.y));
+            }
+            changes
+        };
+        Self::apply_pointer_states(changes);
+    }
+    fn root(&self) -> Widget {
+        self.root.get().and_then(Weak::upgrade).unwrap()
+    }
+
+    /// Sends all pointer events to `widget` until [`release_pointer`](Self::release_pointer) is
+    /// called, regardless of where the pointer is.
+    pub fn grab_pointer(&self, widget: Widget) {
+        let mut state = self.state.borrow_mut();
+        state.grab = Some(widget);
+        state.explicit_grab = true;
+    }
+    pub fn release_pointer(&self) {
+        let mut state = self.state.borrow_mut();
+        state.grab = None;
+        state.explicit_grab = false;
+    }
+    pub fn pointer_grab(&self) -> Option<Widget> {
+        self.state.borrow().grab.clone()
+    }
+
+    pub fn focused(&self) -> Option<Widget> {
+        self.state.borrow().focus.clone()
+    }
+    /// Moves keyboard focus to `widget`, sending [`FocusOut`](signal::FocusOut) to the previously
+    /// focused widget and [`FocusIn`](signal::FocusIn) to the new one. Returns `false` if `widget`
+    /// cannot take focus.
+    pub fn set_focus(&self, widget: Option<Widget>) -> bool {
+        if let Some(widget) = widget.as_ref() {
+            if !widget.can_focus() {
+                return false;
+            }
+        }
+        let old_focus = {
+            let mut state = self.state.borrow_mut();
+            if same_widget(widget.as_ref(), state.focus.as_ref()) {
+                return true;
+            }
+            std::mem::replace(&mut state.focus, widget.clone())
+        };
+        if let Some(old_focus) = old_focus.as_ref() {
+            self.damage_widget(old_focus);
+        }
+        if let Some(widget) = widget.as_ref() {
+            self.damage_widget(widget);
+        }
+        if let Some(old_focus) = old_focus {
+            self.dispatch_event(&old_focus, signal::FocusOut);
+        }
+        if let Some(widget) = widget {
+            self.dispatch_event(&widget, signal::FocusIn);
+        }
+        true
+    }
+    fn tab_order(&self) -> Vec<Widget> {
+        fn collect(widget: &Widget, order: &mut Vec<Widget>) {
+            if widget.can_focus() {
+                order.push(widget.clone());
+            }
+            for child in widget.children().iter() {
+                collect(child, order);
+            }
+        }
+        let mut order = Vec::new();
+        collect(&self.root(), &mut order);
+        order.sort_by_key(|widget| widget.tab_index().unwrap_or(u32::MAX));
+        order
+    }
+    fn move_focus(&self, backwards: bool) {
+        let order = self.tab_order();
+        if order.is_empty() {
+            return;
+        }
+        let focus = self.focused();
+        let current = order
+            .iter()
+            .position(|widget| same_widget(Some(widget), focus.as_ref()));
+        let next = match (current, backwards) {
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+        };
+        self.set_focus(Some(order[next].clone()));
+    }
+    pub fn focus_next(&self) {
+        self.move_focus(false);
+    }
+    pub fn focus_prev(&self) {
+        self.move_focus(true);
+    }
+
+    /// Schedules a layout pass for the next [`emit_layout`](Self::emit_layout). Changes that can
+    /// move widgets also damage the whole window.
+    pub fn mark_needs_layout(&self) {
+        self.needs_layout.set(true);
+        self.damage_all();
+    }
+    pub fn needs_layout(&self) -> bool {
+        self.needs_layout.get()
+    }
+    /// The number of layout passes computed so far.
+    pub fn layout_count(&self) -> u64 {
+        self.layout_count.get()
     }
-    pub fn check_dirty(&self) -> bool {
-        self.dirty.replace(false)
+    /// Marks `rect`, in window coordinates, as needing a repaint.
+    pub fn damage(&self, rect: DamageRect) {
+        if !self.full_damage.get() {
+            self.damage.borrow_mut().add(rect);
+        }
+    }
+    /// Marks the visible part of `widget` as needing a repaint. Does nothing if `widget` is not
+    /// in this gui's widget tree.
+    pub fn damage_widget(&self, widget: &Widget) {
+        if self.full_damage.get() {
+            return;
+        }
+        if let Some(rect) = self.visible_rect(widget) {
+            self.damage.borrow_mut().add(rect);
+        }
+    }
+    pub fn damage_all(&self) {
+        self.full_damage.set(true);
+        self.damage.borrow_mut().clear();
+    }
+    pub fn has_damage(&self) -> bool {
+        self.full_damage.get() || !self.damage.borrow().is_empty()
+    }
+    /// Returns the areas that need a repaint since the last call, and clears them.
+    pub fn take_damage(&self) -> DamageRegion {
+        let mut damage = self.damage.take();
+        if self.full_damage.replace(false) {
+            damage.add(self.window_rect());
+        }
+        damage
+    }
+    fn window_rect(&self) -> DamageRect {
+        let mut size = self
+            .root
+            .get()
+            .and_then(Weak::upgrade)
+            .map(|root| self.layout.borrow().layout(root.node()).unwrap().size)
+            .unwrap_or(Size::ZERO);
+        if let Some(window_size) = self.window_size.get() {
+            size.width = size.width.max(window_size.width);
+            size.height = size.height.max(window_size.height);
+        }
+        DamageRect::new(Point::ZERO, size)
     }
+    // Grows `rect`, the bounds of `widget`, by the reach of its shadow.
+    fn paint_rect(widget: &Widget, rect: DamageRect) -> DamageRect {
+        let Some(outset) = widget
+            .visual()
+            .as_ref()
+            .and_then(|visual| visual.shadow)
+            .map(|shadow| shadow.outset())
+        else {
+            return rect;
+        };
+        DamageRect::new(
+            Point {
+                x: rect.origin.x - outset.left,
+                y: rect.origin.y - outset.top,
+            },
+            Size {
+                width: rect.size.width + outset.left + outset.right,
+                height: rect.size.height + outset.top + outset.bottom,
+            },
+        )
+    }
+    // The window area covered by `widget`, clipped by its ancestors. `None` if the widget is
+    // clipped away or not attached to the root.
+    fn visible_rect(&self, widget: &Widget) -> Option<DamageRect> {
+        let root = self.root.get().and_then(Weak::upgrade)?;
+        let rect_of = |widget: &Widget| {
+            let size = self.layout.borrow().layout(widget.node()).unwrap().size;
+            DamageRect::new(self.window_position(widget), size)
+        };
+        let mut rect = Self::paint_rect(widget, rect_of(widget));
+        let mut top = widget.clone();
+        for ancestor in std::iter::successors(widget.parent(), |widget| widget.parent()) {
+            if ancestor.overflow() == Overflow::Clip {
+                rect = rect.intersection(&rect_of(&ancestor))?;
+            }
+            top = ancestor;
+        }
+        (top.node() == root.node() && !rect.is_empty()).then_some(rect)
+    }
+
+    /// Draws the whole widget tree and clears the damage.
     pub fn draw(&self, context: &mut dyn GraphicsContext, root: widget::Container) {
-        self.dirty.set(false);
-        self.draw_widget(context, &root.into());
+        self.take_damage();
+        self.draw_widget(context, &root.into(), Point::ZERO, None);
+        self.draw_tooltip(context, None);
+    }
+    /// Draws only the widgets intersecting `region`. The caller is expected to clip `context` to
+    /// `region`, usually after getting it from [`take_damage`](Self::take_damage).
+    pub fn draw_region(
+        &self,
+        context: &mut dyn GraphicsContext,
+        root: widget::Container,
+        region: &DamageRegion,
+    ) {
+        self.draw_widget(context, &root.into(), Point::ZERO, Some(region));
+        self.draw_tooltip(context, Some(region));
     }
 
+    /// Computes the layout if anything layout-relevant changed or `available_space` differs from
+    /// the previous call.
     pub fn emit_layout(self: &Rc<Self>, available_space: Option<Size<f32>>) {
         self.signals
             .emit(self.clone(), signal::Layout(available_space));
@@ -271,49 +1291,224 @@ impl Gui {
     pub fn emit_pointer_button(self: &Rc<Self>, button: signal::PointerButton) {
         self.signals.emit(self.clone(), button);
     }
+    pub fn emit_scroll(self: &Rc<Self>, dx: f32, dy: f32) {
+        self.signals.emit(self.clone(), signal::Scroll { dx, dy });
+    }
+    /// Sends a key signal to the focused widget, then to the handlers connected with
+    /// [`connect_key`](Self::connect_key) unless the widget stopped propagation.
+    pub fn emit_key<S>(self: &Rc<Self>, key: S)
+    where
+        S: signal::KeySignal,
+    {
+        self.signals.emit(self.clone(), key);
+    }
+    pub fn connect_key<S, F>(&self, handler: F) -> Connection
+    where
+        S: signal::KeySignal,
+        F: FnMut(Rc<Self>, S) + 'static,
+    {
+        self.key_handlers.connect(handler)
+    }
+    // Returns `false` if the focused widget stopped propagation.
+    fn route_key<S>(self: &Rc<Self>, key: S) -> bool
+    where
+        S: signal::KeySignal,
+    {
+        if let Some(focus) = self.focused() {
+            if self.dispatch_event(&focus, key.clone()) {
+                return false;
+            }
+        }
+        self.key_handlers.emit(self.clone(), key);
+        true
+    }
+
+    /// Sends `signal` through the widget tree as an [`Event`]: first down from the root to
+    /// `target` (capture), then back up to the root (bubble). Returns `true` if a handler stopped
+    /// propagation.
+    pub fn dispatch_event<S>(&self, target: &Widget, signal: S) -> bool
+    where
+        S: Signal,
+    {
+        let path: Vec<Widget> =
+            std::iter::successors(Some(target.clone()), |widget| widget.parent()).collect();
+        let mut event = Event {
+            signal,
+            phase: EventPhase::Capture,
+            target: target.clone(),
+            stopped: Rc::default(),
+        };
+        for widget in path.iter().skip(1).rev() {
+            widget.events().emit(widget.clone(), event.clone());
+            if event.is_propagation_stopped() {
+                return true;
+            }
+        }
+        event.phase = EventPhase::Target;
+        target.events().emit(target.clone(), event.clone());
+        if event.is_propagation_stopped() {
+            return true;
+        }
+        event.phase = EventPhase::Bubble;
+        for widget in path.iter().skip(1) {
+            widget.events().emit(widget.clone(), event.clone());
+            if event.is_propagation_stopped() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the position of `widget`'s top-left corner in window coordinates.
+    pub fn window_position(&self, widget: &Widget) -> Point<f32> {
+        let layout_tree = self.layout.borrow();
+        let mut position = layout_tree.layout(widget.node()).unwrap().location;
+        for ancestor in std::iter::successors(widget.parent(), |widget| widget.parent()) {
+            let location = layout_tree.layout(ancestor.node()).unwrap().location;
+            let offset = ancestor.scroll_offset();
+            position.x += location.x - offset.x;
+            position.y += location.y - offset.y;
+        }
+        position
+    }
+    pub fn pointer_position(&self) -> Option<Point<f32>> {
+        self.state.borrow().pointer
+    }
 
-    fn hit_highlightable_widget(&self, mut x: f32, mut y: f32, widget: &Widget) -> Option<Widget> {
+    fn hit_widget(
+        &self,
+        mut x: f32,
+        mut y: f32,
+        widget: &Widget,
+        filter: &dyn Fn(&Widget) -> bool,
+    ) -> Option<Widget> {
+        if widget.visibility() != Visibility::Visible {
+            return None;
+        }
         let layout_tree = self.layout.borrow();
         let layout = layout_tree.layout(widget.node()).unwrap();
         x -= layout.location.x;
         y -= layout.location.y;
         if x >= 0.0 && y >= 0.0 && x < layout.size.width && y < layout.size.height {
-            for child in widget.children().iter().rev() {
-                if let Some(hit_widget) = self.hit_highlightable_widget(x, y, child) {
-                    return Some(hit_widget);
+            if widget.hit_children(Point { x, y }) {
+                let offset = widget.scroll_offset();
+                for child in widget.children().iter().rev() {
+                    if let Some(hit_widget) =
+                        self.hit_widget(x + offset.x, y + offset.y, child, filter)
+                    {
+                        return Some(hit_widget);
+                    }
                 }
             }
-            if widget.can_highlight() {
+            if filter(widget) {
                 return Some(widget.clone());
             }
         }
         None
     }
 
-    fn draw_widget(&self, context: &mut dyn GraphicsContext, widget: &Widget) {
-        context.save();
+    // Draws the shadow, background and border of a widget.
+    fn draw_visual(
+        context: &mut dyn GraphicsContext,
+        visual: &VisualStyle,
+        colors: &VisualColors,
+        size: Size<f32>,
+        border: Rect<LengthPercentage>,
+    ) {
+        let radius = visual.corner_radius.clamp(size);
+        if let Some(shadow) = visual.shadow.as_ref() {
+            context.draw_shadow(Point::ZERO, size, radius, shadow);
+        }
+        if let Some(background) = colors.background {
+            context.set_color(background);
+            if radius.is_zero() {
+                context.draw_rect(Point::ZERO, size);
+            } else {
+                context.draw_rounded_rect(Point::ZERO, size, radius);
+            }
+        }
+        if let Some(gradient) = visual.gradient.as_ref() {
+            context.draw_gradient(Point::ZERO, size, radius, gradient);
+        }
+        let sides = visual.border_sides;
+        let uniform = [sides.left, sides.right, sides.top, sides.bottom] == [None; 4];
+        if uniform && radius.is_zero() && visual.border_style == BorderStyle::Solid {
+            if let Some(color) = colors.border {
+                context.set_color(color);
+                context.draw_border(size, border);
+            }
+            return;
+        }
+        let widths = resolve_border(size, border);
+        let colors = Rect {
+            left: sides.left.or(colors.border),
+            right: sides.right.or(colors.border),
+            top: sides.top.or(colors.border),
+            bottom: sides.bottom.or(colors.border),
+        };
+        context.draw_border_sides(size, widths, colors, radius, visual.border_style);
+    }
+    // `origin` is the window position that `widget`'s location is relative to.
+    fn draw_widget(
+        &self,
+        context: &mut dyn GraphicsContext,
+        widget: &Widget,
+        origin: Point<f32>,
+        region: Option<&DamageRegion>,
+    ) {
+        if widget.visibility() != Visibility::Visible {
+            return;
+        }
         let layout_tree = self.layout.borrow();
         let layout = layout_tree.layout(widget.node()).unwrap();
+        let origin = Point {
+            x: origin.x + layout.location.x,
+            y: origin.y + layout.location.y,
+        };
+        let damaged = region
+            .map(|region| {
+                region.intersects(&Self::paint_rect(
+                    widget,
+                    DamageRect::new(origin, layout.size),
+                ))
+            })
+            .unwrap_or(true);
+        if !damaged && widget.overflow() == Overflow::Clip {
+            return;
+        }
+        context.save();
         context.translate(layout.location.x, layout.location.y);
 
-        if let Some(visual) = widget.visual().as_ref() {
-            if let Some(background) = visual.background {
-                context.set_color(background);
-                context.draw_rect(Point::ZERO, layout.size);
-            }
-            if let Some(border) = visual.border {
-                context.set_color(border);
-                let border_style = layout_tree.style(widget.node()).unwrap().border;
-                context.draw_border(layout.size, border_style);
+        let colors = widget.visual_colors();
+        if let (true, Some(colors)) = (damaged, colors.as_ref()) {
+            if let Some(visual) = widget.visual().as_ref() {
+                let border = layout_tree.style(widget.node()).unwrap().border;
+                Self::draw_visual(context, visual, colors, layout.size, border);
             }
-            if let Some(foreground) = visual.foreground {
+            if let Some(foreground) = colors.foreground {
                 context.set_color(foreground);
                 widget.draw(context, layout.size);
             }
         }
 
+        context.save();
+        if widget.overflow() == Overflow::Clip {
+            context.clip_rect(Point::ZERO, layout.size);
+        }
+        let offset = widget.scroll_offset();
+        context.translate(-offset.x, -offset.y);
+        let children_origin = Point {
+            x: origin.x - offset.x,
+            y: origin.y - offset.y,
+        };
         for child in widget.children().iter() {
-            self.draw_widget(context, child);
+            self.draw_widget(context, child, children_origin, region);
+        }
+        context.restore();
+
+        if let (true, Some(foreground)) = (damaged, colors.and_then(|colors| colors.foreground)) {
+            context.set_color(foreground);
+            widget.draw_overlay(context, layout.size);
         }
 
         context.restore();
@@ -5,6 +5,9 @@ use crate::signal::*;
 pub trait Model<T> {
     fn get(&self) -> T;
     fn set(self: Rc<Self>, val: T);
+    /// Connects a handler that is called after every change. Unlike
+    /// [`CellModel::connect_change`], it isn't called right away.
+    fn connect_changed(&self, handler: Box<dyn FnMut()>) -> Connection;
 }
 
 pub struct CellModel<T>
@@ -26,6 +29,9 @@ where
         self.data.set(val);
         self.signals.emit(self.clone(), Change);
     }
+    fn connect_changed(&self, mut handler: Box<dyn FnMut()>) -> Connection {
+        self.signals.connect(move |_, Change| handler())
+    }
 }
 
 impl<T> CellModel<T>
@@ -38,12 +44,16 @@ where
             signals: Signals::new(),
         })
     }
-    pub fn connect_change<F>(self: &Rc<Self>, mut handler: F)
+    pub fn connect_change<F>(self: &Rc<Self>, mut handler: F) -> Connection
     where
         F: FnMut(Rc<Self>, Change) + 'static,
     {
         handler(self.clone(), Change);
-        self.signals.connect(handler);
+        self.signals.connect(handler)
+    }
+    /// Resolves on the next change, for use in async code.
+    pub fn changed(&self) -> SignalFuture<Change> {
+        self.signals.next()
     }
 }
 
@@ -91,4 +101,7 @@ where
             self.model.clone().set(self.value.clone());
         }
     }
+    fn connect_changed(&self, handler: Box<dyn FnMut()>) -> Connection {
+        self.model.connect_changed(handler)
+    }
 }
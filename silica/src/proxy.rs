@@ -0,0 +1,80 @@
+use std::{
+    collections::VecDeque,
+    rc::Rc,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::{Connection, Gui, Signal};
+
+type Posted = Box<dyn FnOnce(&Rc<Gui>) + Send>;
+type Waker = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct ProxyShared {
+    queue: Mutex<VecDeque<Posted>>,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// A handle for other threads to run code on the GUI thread, created with [`Gui::proxy`].
+#[derive(Clone)]
+pub struct GuiProxy {
+    shared: Weak<ProxyShared>,
+}
+
+impl GuiProxy {
+    /// Queues `f` to run on the GUI thread between events, and wakes the event loop. Returns
+    /// `false` if the [`Gui`] no longer exists.
+    pub fn post<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&Rc<Gui>) + Send + 'static,
+    {
+        let Some(shared) = self.shared.upgrade() else {
+            return false;
+        };
+        shared.queue.lock().unwrap().push_back(Box::new(f));
+        let waker = shared.waker.lock().unwrap().clone();
+        if let Some(waker) = waker {
+            waker();
+        }
+        true
+    }
+    /// Sends `signal` to the handlers connected with [`Gui::connect_user_event`].
+    pub fn send_user_event<S>(&self, signal: S) -> bool
+    where
+        S: Signal + Send,
+    {
+        self.post(move |gui| gui.user_events.emit(gui.clone(), signal))
+    }
+}
+
+impl Gui {
+    pub fn proxy(&self) -> GuiProxy {
+        GuiProxy {
+            shared: Arc::downgrade(&self.proxy),
+        }
+    }
+    /// Sets the function that [`GuiProxy`] calls from any thread after queueing work. Backends
+    /// use it to wake their event loop, which then calls
+    /// [`emit_proxy_events`](Self::emit_proxy_events).
+    pub fn set_proxy_waker<F>(&self, waker: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.proxy.waker.lock().unwrap() = Some(Arc::new(waker));
+    }
+    /// Runs the closures posted through [`GuiProxy`], in order. Closures posted while running
+    /// wait for the next call.
+    pub fn emit_proxy_events(self: &Rc<Self>) {
+        let posted = std::mem::take(&mut *self.proxy.queue.lock().unwrap());
+        for f in posted {
+            f(self);
+        }
+    }
+    pub fn connect_user_event<S, F>(&self, handler: F) -> Connection
+    where
+        S: Signal,
+        F: FnMut(Rc<Self>, S) + 'static,
+    {
+        self.user_events.connect(handler)
+    }
+}
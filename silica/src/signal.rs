@@ -1,13 +1,97 @@
-use std::cell::RefCell;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
 
 use taffy::prelude::Size;
 use type_map::TypeMap;
 
-pub trait Signal: 'static {}
+pub trait Signal: Clone + 'static {}
 
+struct Handler<T, S> {
+    id: u64,
+    connected: Cell<bool>,
+    func: RefCell<Box<dyn FnMut(T, S)>>,
+}
+
+type HandlerList<T, S> = Vec<Rc<Handler<T, S>>>;
+type PendingEmit<T> = Box<dyn FnOnce(&Signals<T>)>;
+
+// The removed handler is handed back so it can be dropped after the `TypeMap` borrow ends.
+fn remove_handler<T, S>(handlers: &mut TypeMap, id: u64) -> Option<Box<dyn Any>>
+where
+    T: 'static,
+    S: Signal,
+{
+    let list = handlers.get_mut::<HandlerList<T, S>>()?;
+    let index = list.iter().position(|handler| handler.id == id)?;
+    let handler = list.remove(index);
+    handler.connected.set(false);
+    Some(Box::new(handler))
+}
+
+/// Handle to a handler connected with [`Signals::connect`].
+///
+/// Dropping a `Connection` leaves the handler connected. Use [`Connection::scoped`] to get a
+/// handle that disconnects the handler when it goes out of scope.
+pub struct Connection {
+    handlers: Weak<RefCell<TypeMap>>,
+    id: u64,
+    remove: fn(&mut TypeMap, u64) -> Option<Box<dyn Any>>,
+}
+
+impl Connection {
+    /// Disconnects the handler. Returns `false` if it was already disconnected.
+    pub fn disconnect(&self) -> bool {
+        if let Some(handlers) = self.handlers.upgrade() {
+            let handler = (self.remove)(&mut handlers.borrow_mut(), self.id);
+            handler.is_some()
+        } else {
+            false
+        }
+    }
+    pub fn scoped(self) -> ScopedConnection {
+        ScopedConnection(Some(self))
+    }
+}
+
+/// A [`Connection`] that disconnects its handler when dropped.
+pub struct ScopedConnection(Option<Connection>);
+
+impl ScopedConnection {
+    /// Turns this back into a plain [`Connection`], keeping the handler connected.
+    pub fn release(mut self) -> Connection {
+        self.0.take().unwrap()
+    }
+    pub fn disconnect(&self) -> bool {
+        self.0.as_ref().map(Connection::disconnect).unwrap_or(false)
+    }
+}
+
+impl Drop for ScopedConnection {
+    fn drop(&mut self) {
+        if let Some(connection) = self.0.take() {
+            connection.disconnect();
+        }
+    }
+}
+
+/// A set of signal handlers, keyed by signal type.
+///
+/// Emission is re-entrant: a handler may connect or disconnect handlers and emit further signals
+/// on the same `Signals`. Signals emitted while an emission is in progress are queued and
+/// delivered, in order, once it finishes. Handlers connected during an emission are first called
+/// on the next one.
 pub struct Signals<T> {
-    handlers: RefCell<TypeMap>,
-    _marker: std::marker::PhantomData<T>,
+    handlers: Rc<RefCell<TypeMap>>,
+    next_id: Cell<u64>,
+    emitting: Cell<bool>,
+    pending: RefCell<VecDeque<PendingEmit<T>>>,
 }
 
 impl<T> Signals<T>
@@ -16,45 +100,282 @@ where
 {
     pub fn new() -> Self {
         Signals {
-            handlers: RefCell::new(TypeMap::new()),
-            _marker: std::marker::PhantomData,
+            handlers: Rc::new(RefCell::new(TypeMap::new())),
+            next_id: Cell::new(0),
+            emitting: Cell::new(false),
+            pending: RefCell::default(),
         }
     }
-    pub fn connect<S, F>(&self, handler: F)
+    /// Connects a handler for signals of type `S`. Handlers are called in the order they were
+    /// connected.
+    pub fn connect<S, F>(&self, handler: F) -> Connection
     where
         S: Signal,
         F: FnMut(T, S) + 'static,
     {
-        let boxed_handler: Box<dyn FnMut(T, S)> = Box::new(handler);
-        self.handlers.borrow_mut().insert(boxed_handler);
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.handlers
+            .borrow_mut()
+            .entry::<HandlerList<T, S>>()
+            .or_insert_with(Vec::new)
+            .push(Rc::new(Handler {
+                id,
+                connected: Cell::new(true),
+                func: RefCell::new(Box::new(handler)),
+            }));
+        Connection {
+            handlers: Rc::downgrade(&self.handlers),
+            id,
+            remove: remove_handler::<T, S>,
+        }
+    }
+    /// Returns a future that resolves to the next signal of type `S` emitted after this call.
+    pub fn next<S>(&self) -> SignalFuture<S>
+    where
+        S: Signal,
+    {
+        let state: Rc<RefCell<NextSignal<S>>> = Rc::default();
+        let connection = self.connect({
+            let state = state.clone();
+            move |_, signal: S| {
+                let mut state = state.borrow_mut();
+                if state.signal.is_none() {
+                    state.signal = Some(signal);
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                }
+            }
+        });
+        SignalFuture {
+            state,
+            connection: connection.scoped(),
+        }
     }
     pub fn emit<S>(&self, source: T, signal: S)
     where
+        T: Clone,
         S: Signal,
     {
-        if let Some(handler) = self.handlers.borrow_mut().get_mut::<Box<dyn FnMut(T, S)>>() {
-            handler(source, signal);
+        if self.emitting.get() {
+            self.pending
+                .borrow_mut()
+                .push_back(Box::new(move |signals: &Self| {
+                    signals.dispatch(source, signal)
+                }));
+            return;
+        }
+        let _guard = EmitGuard(self);
+        self.emitting.set(true);
+        self.dispatch(source, signal);
+        loop {
+            let pending = self.pending.borrow_mut().pop_front();
+            match pending {
+                Some(pending) => pending(self),
+                None => break,
+            }
+        }
+    }
+    fn dispatch<S>(&self, source: T, signal: S)
+    where
+        T: Clone,
+        S: Signal,
+    {
+        let handlers = self
+            .handlers
+            .borrow()
+            .get::<HandlerList<T, S>>()
+            .cloned()
+            .unwrap_or_default();
+        for handler in handlers {
+            if handler.connected.get() {
+                (handler.func.borrow_mut())(source.clone(), signal.clone());
+            }
         }
     }
 }
 
+// Ends an emission even if a handler panics. Signals queued behind the panicking handler are
+// dropped so a later emission does not deliver them out of context.
+struct EmitGuard<'a, T>(&'a Signals<T>);
+
+impl<T> Drop for EmitGuard<'_, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.pending.borrow_mut().clear();
+        }
+        self.0.emitting.set(false);
+    }
+}
+
+struct NextSignal<S> {
+    signal: Option<S>,
+    waker: Option<Waker>,
+}
+
+impl<S> Default for NextSignal<S> {
+    fn default() -> Self {
+        NextSignal {
+            signal: None,
+            waker: None,
+        }
+    }
+}
+
+/// Future returned by [`Signals::next`]. Dropping it disconnects its handler.
+pub struct SignalFuture<S> {
+    state: Rc<RefCell<NextSignal<S>>>,
+    connection: ScopedConnection,
+}
+
+impl<S> Future for SignalFuture<S> {
+    type Output = S;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<S> {
+        let this = self.get_mut();
+        let signal = this.state.borrow_mut().signal.take();
+        match signal {
+            Some(signal) => {
+                this.connection.disconnect();
+                Poll::Ready(signal)
+            }
+            None => {
+                this.state.borrow_mut().waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Default for Signals<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
 pub struct Change;
 impl Signal for Change {}
 
+#[derive(Clone)]
 pub struct Activate;
 impl Signal for Activate {}
 
+/// Emitted when an animation runs to completion.
+#[derive(Clone)]
+pub struct Finished;
+impl Signal for Finished {}
+
+/// Emitted by the [`Gui`](crate::Gui) after [`set_theme`](crate::Gui::set_theme).
+#[derive(Clone)]
+pub struct ThemeChanged;
+impl Signal for ThemeChanged {}
+
+#[derive(Clone)]
 pub struct Layout(pub Option<Size<f32>>);
 impl Signal for Layout {}
 
+#[derive(Clone)]
 pub struct PointerMotion {
     pub x: f32,
     pub y: f32,
 }
 impl Signal for PointerMotion {}
 
+#[derive(Clone, Copy)]
 pub enum PointerButton {
     Primary(bool),
     Secondary(bool),
+    Middle(bool),
 }
 impl Signal for PointerButton {}
+
+/// A scroll wheel or touchpad scroll, in lines. Positive values scroll right and down.
+#[derive(Clone)]
+pub struct Scroll {
+    pub dx: f32,
+    pub dy: f32,
+}
+impl Signal for Scroll {}
+
+/// Sent through the widget tree on a secondary click, at the pointer position in window
+/// coordinates.
+#[derive(Clone)]
+pub struct ContextMenu {
+    pub x: f32,
+    pub y: f32,
+}
+impl Signal for ContextMenu {}
+
+#[derive(Clone)]
+pub struct FocusIn;
+impl Signal for FocusIn {}
+
+#[derive(Clone)]
+pub struct FocusOut;
+impl Signal for FocusOut {}
+
+/// A logical key, independent of the keyboard layout.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    /// A key that produces a character, such as a letter, digit or punctuation.
+    Character(char),
+    Space,
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Left,
+    Right,
+    Up,
+    Down,
+    Shift,
+    Control,
+    Alt,
+    Logo,
+    CapsLock,
+    /// A function key, `F(1)` through `F(24)`.
+    F(u8),
+    Unidentified,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+/// Signals emitted with [`Gui::emit_key`](crate::Gui::emit_key).
+pub trait KeySignal: Signal {}
+
+#[derive(Clone)]
+pub struct KeyPress {
+    pub key: Key,
+    pub modifiers: Modifiers,
+    /// Set if this press was generated by key repeat.
+    pub repeat: bool,
+    /// The text produced by the key, if any.
+    pub text: Option<String>,
+}
+impl Signal for KeyPress {}
+impl KeySignal for KeyPress {}
+
+#[derive(Clone)]
+pub struct KeyRelease {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+impl Signal for KeyRelease {}
+impl KeySignal for KeyRelease {}
@@ -0,0 +1,755 @@
+use std::{fmt, io, path::Path, rc::Rc, time::Duration};
+
+use taffy::prelude::*;
+
+use crate::{
+    BorderStyle, Color, CornerRadius, Gui, StyleColor, ThemeColor, VisualStyle, Widget,
+    WidgetDataUntyped,
+};
+
+/// Rules that set the appearance and layout of widgets, written in a subset of CSS:
+///
+/// ```css
+/// /* Selectors match the widget type, classes set with `add_class`, the id set with `set_id`,
+///    ancestors, and a state pseudo-class on the last part. */
+/// Button, .tool:hover { background: #3d5a80; }
+/// #sidebar Label { color: theme(foreground-disable); padding: 2px 4px; }
+/// Container.toolbar { flex-direction: row; gap: 4px; min-height: 32px; }
+/// ```
+///
+/// The visual properties are `background`, `border-color`, `color` (the foreground),
+/// `border-radius`, `border-style` and `transition`. The layout properties are `padding`,
+/// `margin`, `border-width`, `gap`, `width`, `height`, `size`, `min-width`, `min-height`,
+/// `min-size`, `max-width`, `max-height`, `max-size`, `flex`, `flex-grow`, `flex-shrink`,
+/// `flex-basis`, `flex-direction`, `flex-wrap`, `align-items` and `justify-content`. Rules with a
+/// pseudo-class (`:hover`, `:pressed`, `:focused`, `:disabled`, `:checked`) may only set colors,
+/// which go into the matching [`VisualStyle`] override.
+///
+/// Install a sheet with [`Gui::set_style_sheet`]. A widget's own layout and visual style are
+/// kept apart from what the sheet sets, and the matching rules are applied on top of them again
+/// whenever the sheet, the widget's classes or id, or its ancestors change.
+#[derive(Clone, Debug, Default)]
+pub struct StyleSheet {
+    rules: Vec<Rule>,
+}
+
+impl StyleSheet {
+    pub fn parse(source: &str) -> Result<StyleSheet, StyleSheetError> {
+        Parser::new(source).parse()
+    }
+    /// Reads a style sheet from a file, see [`parse`](Self::parse).
+    pub fn load(path: impl AsRef<Path>) -> Result<StyleSheet, StyleSheetError> {
+        let source = std::fs::read_to_string(path).map_err(StyleSheetError::Io)?;
+        StyleSheet::parse(&source)
+    }
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+    /// The rules that match `widget`, from the lowest precedence to the highest.
+    pub fn matching_rules(&self, widget: &Widget) -> Vec<&Rule> {
+        let mut rules: Vec<&Rule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.selector.matches(widget))
+            .collect();
+        rules.sort_by_key(|rule| (rule.specificity(), rule.order));
+        rules
+    }
+}
+
+/// A selector and the declarations it applies. Selectors separated by commas are parsed into
+/// separate rules.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    selector: Selector,
+    declarations: Vec<Declaration>,
+    line: usize,
+    order: usize,
+}
+
+impl Rule {
+    fn properties(&self) -> impl Iterator<Item = &Property> {
+        self.declarations.iter().flat_map(|d| &d.properties)
+    }
+    pub(crate) fn apply_layout(&self, layout: &mut Style) {
+        for property in self.properties().filter(|property| !property.is_visual()) {
+            property.apply_layout(layout);
+        }
+    }
+    pub(crate) fn apply_visual(&self, visual: &mut Option<VisualStyle>) {
+        for property in self.properties().filter(|property| property.is_visual()) {
+            let visual = visual.get_or_insert_with(VisualStyle::default);
+            property.apply_visual(visual, self.selector.state);
+        }
+    }
+    pub fn selector(&self) -> &str {
+        &self.selector.text
+    }
+    /// The line of the style sheet the rule starts on, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// The number of ids, of classes and pseudo-classes, and of widget types in the selector.
+    /// Rules with a higher specificity win, then rules that come later.
+    pub fn specificity(&self) -> (usize, usize, usize) {
+        self.selector.specificity()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.selector.text)?;
+        for declaration in &self.declarations {
+            write!(f, " {}: {};", declaration.name, declaration.value)?;
+        }
+        write!(f, " }} (line {})", self.line)
+    }
+}
+
+/// The error returned when a style sheet can't be loaded.
+#[derive(Debug)]
+pub enum StyleSheetError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for StyleSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleSheetError::Io(err) => write!(f, "failed to read style sheet: {err}"),
+            StyleSheetError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for StyleSheetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StyleSheetError::Io(err) => Some(err),
+            StyleSheetError::Parse { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StateClass {
+    Hover,
+    Pressed,
+    Focused,
+    Disabled,
+    Checked,
+}
+
+// Type, id and classes that must all match one widget.
+#[derive(Clone, Debug, Default)]
+struct Compound {
+    type_name: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+impl Compound {
+    fn matches(&self, widget: &dyn WidgetDataUntyped) -> bool {
+        if let Some(type_name) = self.type_name.as_deref() {
+            if type_name != widget.type_name() {
+                return false;
+            }
+        }
+        if let Some(id) = self.id.as_deref() {
+            if widget.id().as_deref() != Some(id) {
+                return false;
+            }
+        }
+        self.classes.iter().all(|class| widget.has_class(class))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Selector {
+    text: String,
+    // Each must match some ancestor, in order from the outermost.
+    ancestors: Vec<Compound>,
+    subject: Compound,
+    state: Option<StateClass>,
+}
+
+impl Selector {
+    fn matches(&self, widget: &Widget) -> bool {
+        if !self.subject.matches(widget.as_ref()) {
+            return false;
+        }
+        // Matching the innermost compound against the nearest ancestor is never worse than
+        // skipping it, since only descendant combinators are supported.
+        let mut ancestors = std::iter::successors(widget.parent(), |widget| widget.parent());
+        self.ancestors
+            .iter()
+            .rev()
+            .all(|compound| ancestors.any(|ancestor| compound.matches(ancestor.as_ref())))
+    }
+    fn specificity(&self) -> (usize, usize, usize) {
+        let compounds = self.ancestors.iter().chain([&self.subject]);
+        let (mut ids, mut classes, mut types) = (0, self.state.is_some() as usize, 0);
+        for compound in compounds {
+            ids += compound.id.is_some() as usize;
+            classes += compound.classes.len();
+            types += compound.type_name.is_some() as usize;
+        }
+        (ids, classes, types)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Declaration {
+    name: String,
+    value: String,
+    // More than one for shorthands.
+    properties: Vec<Property>,
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Background(Option<StyleColor>),
+    BorderColor(Option<StyleColor>),
+    Foreground(Option<StyleColor>),
+    Transition(Duration),
+    CornerRadius(CornerRadius),
+    BorderStyle(BorderStyle),
+    Padding(Rect<LengthPercentage>),
+    Margin(Rect<LengthPercentageAuto>),
+    BorderWidth(Rect<LengthPercentage>),
+    Gap(Size<LengthPercentage>),
+    Size(Size<Option<Dimension>>),
+    MinSize(Size<Option<Dimension>>),
+    MaxSize(Size<Option<Dimension>>),
+    FlexGrow(f32),
+    FlexShrink(f32),
+    FlexBasis(Dimension),
+    FlexDirection(FlexDirection),
+    FlexWrap(FlexWrap),
+    AlignItems(AlignItems),
+    JustifyContent(JustifyContent),
+}
+
+impl Property {
+    fn parse(name: &str, value: &str) -> Result<Vec<Property>, String> {
+        let values: Vec<&str> = value.split_whitespace().collect();
+        let width = |dimension| Size {
+            width: Some(dimension),
+            height: None,
+        };
+        let height = |dimension| Size {
+            width: None,
+            height: Some(dimension),
+        };
+        let property = match name {
+            "background" => Property::Background(parse_color(value)?),
+            "border-color" => Property::BorderColor(parse_color(value)?),
+            "color" => Property::Foreground(parse_color(value)?),
+            "transition" => Property::Transition(parse_duration(value)?),
+            "border-radius" => Property::CornerRadius(parse_corner_radius(&values)?),
+            "border-style" => Property::BorderStyle(match value {
+                "solid" => BorderStyle::Solid,
+                "dashed" => BorderStyle::Dashed,
+                "dotted" => BorderStyle::Dotted,
+                _ => return Err(format!("invalid border style {value:?}")),
+            }),
+            "padding" => Property::Padding(parse_edges(&values, parse_length_percentage)?),
+            "margin" => Property::Margin(parse_edges(&values, parse_length_percentage_auto)?),
+            "border-width" => Property::BorderWidth(parse_edges(&values, parse_length_percentage)?),
+            "gap" => {
+                let [row, column] = parse_pair(&values, parse_length_percentage)?;
+                Property::Gap(Size {
+                    width: column,
+                    height: row,
+                })
+            }
+            "width" => Property::Size(width(parse_dimension(value)?)),
+            "height" => Property::Size(height(parse_dimension(value)?)),
+            "size" => Property::Size(parse_size(&values)?),
+            "min-width" => Property::MinSize(width(parse_dimension(value)?)),
+            "min-height" => Property::MinSize(height(parse_dimension(value)?)),
+            "min-size" => Property::MinSize(parse_size(&values)?),
+            "max-width" => Property::MaxSize(width(parse_dimension(value)?)),
+            "max-height" => Property::MaxSize(height(parse_dimension(value)?)),
+            "max-size" => Property::MaxSize(parse_size(&values)?),
+            "flex" => {
+                let mut properties = Vec::new();
+                match values.as_slice() {
+                    [grow] => properties.push(Property::FlexGrow(parse_number(grow)?)),
+                    [grow, shrink] | [grow, shrink, _] => {
+                        properties.push(Property::FlexGrow(parse_number(grow)?));
+                        properties.push(Property::FlexShrink(parse_number(shrink)?));
+                    }
+                    _ => return Err(format!("expected 1 to 3 values, found {value:?}")),
+                }
+                if let [_, _, basis] = values.as_slice() {
+                    properties.push(Property::FlexBasis(parse_dimension(basis)?));
+                }
+                return Ok(properties);
+            }
+            "flex-grow" => Property::FlexGrow(parse_number(value)?),
+            "flex-shrink" => Property::FlexShrink(parse_number(value)?),
+            "flex-basis" => Property::FlexBasis(parse_dimension(value)?),
+            "flex-direction" => Property::FlexDirection(match value {
+                "row" => FlexDirection::Row,
+                "column" => FlexDirection::Column,
+                "row-reverse" => FlexDirection::RowReverse,
+                "column-reverse" => FlexDirection::ColumnReverse,
+                _ => return Err(format!("invalid flex direction {value:?}")),
+            }),
+            "flex-wrap" => Property::FlexWrap(match value {
+                "nowrap" => FlexWrap::NoWrap,
+                "wrap" => FlexWrap::Wrap,
+                "wrap-reverse" => FlexWrap::WrapReverse,
+                _ => return Err(format!("invalid flex wrap {value:?}")),
+            }),
+            "align-items" => Property::AlignItems(match value {
+                "start" => AlignItems::Start,
+                "end" => AlignItems::End,
+                "flex-start" => AlignItems::FlexStart,
+                "flex-end" => AlignItems::FlexEnd,
+                "center" => AlignItems::Center,
+                "baseline" => AlignItems::Baseline,
+                "stretch" => AlignItems::Stretch,
+                _ => return Err(format!("invalid alignment {value:?}")),
+            }),
+            "justify-content" => Property::JustifyContent(match value {
+                "start" => JustifyContent::Start,
+                "end" => JustifyContent::End,
+                "flex-start" => JustifyContent::FlexStart,
+                "flex-end" => JustifyContent::FlexEnd,
+                "center" => JustifyContent::Center,
+                "stretch" => JustifyContent::Stretch,
+                "space-between" => JustifyContent::SpaceBetween,
+                "space-evenly" => JustifyContent::SpaceEvenly,
+                "space-around" => JustifyContent::SpaceAround,
+                _ => return Err(format!("invalid justification {value:?}")),
+            }),
+            _ => return Err(format!("unknown property {name:?}")),
+        };
+        Ok(vec![property])
+    }
+    fn is_color(&self) -> bool {
+        matches!(
+            self,
+            Property::Background(_) | Property::BorderColor(_) | Property::Foreground(_)
+        )
+    }
+    fn is_visual(&self) -> bool {
+        self.is_color()
+            || matches!(
+                self,
+                Property::Transition(_) | Property::CornerRadius(_) | Property::BorderStyle(_)
+            )
+    }
+    // Only colors can be set for a state, which the parser checks.
+    fn apply_visual(&self, visual: &mut VisualStyle, state: Option<StateClass>) {
+        match self {
+            Property::Transition(duration) => return visual.transition = *duration,
+            Property::CornerRadius(radius) => return visual.corner_radius = *radius,
+            Property::BorderStyle(style) => return visual.border_style = *style,
+            _ => {}
+        }
+        let mut colors = match state {
+            None => visual.normal(),
+            Some(StateClass::Hover) => visual.hover,
+            Some(StateClass::Pressed) => visual.pressed,
+            Some(StateClass::Focused) => visual.focused,
+            Some(StateClass::Disabled) => visual.disabled,
+            Some(StateClass::Checked) => visual.checked,
+        };
+        match self {
+            Property::Background(color) => colors.background = *color,
+            Property::BorderColor(color) => colors.border = *color,
+            Property::Foreground(color) => colors.foreground = *color,
+            _ => unreachable!(),
+        }
+        let target = match state {
+            None => {
+                visual.background = colors.background;
+                visual.border = colors.border;
+                visual.foreground = colors.foreground;
+                return;
+            }
+            Some(StateClass::Hover) => &mut visual.hover,
+            Some(StateClass::Pressed) => &mut visual.pressed,
+            Some(StateClass::Focused) => &mut visual.focused,
+            Some(StateClass::Disabled) => &mut visual.disabled,
+            Some(StateClass::Checked) => &mut visual.checked,
+        };
+        *target = colors;
+    }
+    fn apply_layout(&self, layout: &mut Style) {
+        let apply_size = |target: &mut Size<Dimension>, size: &Size<Option<Dimension>>| {
+            target.width = size.width.unwrap_or(target.width);
+            target.height = size.height.unwrap_or(target.height);
+        };
+        match self {
+            Property::Padding(padding) => layout.padding = *padding,
+            Property::Margin(margin) => layout.margin = *margin,
+            Property::BorderWidth(border) => layout.border = *border,
+            Property::Gap(gap) => layout.gap = *gap,
+            Property::Size(size) => apply_size(&mut layout.size, size),
+            Property::MinSize(size) => apply_size(&mut layout.min_size, size),
+            Property::MaxSize(size) => apply_size(&mut layout.max_size, size),
+            Property::FlexGrow(grow) => layout.flex_grow = *grow,
+            Property::FlexShrink(shrink) => layout.flex_shrink = *shrink,
+            Property::FlexBasis(basis) => layout.flex_basis = *basis,
+            Property::FlexDirection(direction) => layout.flex_direction = *direction,
+            Property::FlexWrap(wrap) => layout.flex_wrap = *wrap,
+            Property::AlignItems(align) => layout.align_items = Some(*align),
+            Property::JustifyContent(justify) => layout.justify_content = Some(*justify),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Result<Option<StyleColor>, String> {
+    if value == "none" {
+        return Ok(None);
+    }
+    if let Some(name) = value
+        .strip_prefix("theme(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let name = name.trim().replace('-', "_");
+        return ThemeColor::from_name(&name)
+            .map(|color| Some(color.into()))
+            .ok_or_else(|| format!("unknown theme color {name:?}"));
+    }
+    Color::parse(value)
+        .map(|color| Some(color.into()))
+        .map_err(|err| err.to_string())
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else {
+        return Err(format!("expected a duration in s or ms, found {value:?}"));
+    };
+    let seconds = parse_number(number)? * scale;
+    Duration::try_from_secs_f32(seconds).map_err(|_| format!("invalid duration {value:?}"))
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number, found {value:?}"))
+}
+
+// Top left, top right, bottom right and bottom left, shortened like in CSS.
+fn parse_corner_radius(values: &[&str]) -> Result<CornerRadius, String> {
+    let values = values
+        .iter()
+        .map(|value| parse_number(value.strip_suffix("px").unwrap_or(value)))
+        .collect::<Result<Vec<f32>, String>>()?;
+    let [top_left, top_right, bottom_right, bottom_left] = match values.as_slice() {
+        [all] => [*all; 4],
+        [first, second] => [*first, *second, *first, *second],
+        [top_left, second, bottom_right] => [*top_left, *second, *bottom_right, *second],
+        [top_left, top_right, bottom_right, bottom_left] => {
+            [*top_left, *top_right, *bottom_right, *bottom_left]
+        }
+        _ => return Err(format!("expected 1 to 4 values, found {}", values.len())),
+    };
+    Ok(CornerRadius {
+        top_left,
+        top_right,
+        bottom_right,
+        bottom_left,
+    })
+}
+
+fn parse_length_percentage(value: &str) -> Result<LengthPercentage, String> {
+    if let Some(percent) = value.strip_suffix('%') {
+        return Ok(LengthPercentage::Percent(parse_number(percent)? / 100.0));
+    }
+    let points = value.strip_suffix("px").unwrap_or(value);
+    Ok(LengthPercentage::Points(parse_number(points)?))
+}
+
+fn parse_length_percentage_auto(value: &str) -> Result<LengthPercentageAuto, String> {
+    if value == "auto" {
+        return Ok(LengthPercentageAuto::Auto);
+    }
+    Ok(match parse_length_percentage(value)? {
+        LengthPercentage::Points(points) => LengthPercentageAuto::Points(points),
+        LengthPercentage::Percent(percent) => LengthPercentageAuto::Percent(percent),
+    })
+}
+
+fn parse_dimension(value: &str) -> Result<Dimension, String> {
+    Ok(match parse_length_percentage_auto(value)? {
+        LengthPercentageAuto::Points(points) => Dimension::Points(points),
+        LengthPercentageAuto::Percent(percent) => Dimension::Percent(percent),
+        LengthPercentageAuto::Auto => Dimension::Auto,
+    })
+}
+
+// One value for both, or two.
+fn parse_pair<T>(values: &[&str], parse: fn(&str) -> Result<T, String>) -> Result<[T; 2], String>
+where
+    T: Copy,
+{
+    match values {
+        [both] => {
+            let both = parse(both)?;
+            Ok([both, both])
+        }
+        [first, second] => Ok([parse(first)?, parse(second)?]),
+        _ => Err(format!("expected 1 or 2 values, found {}", values.len())),
+    }
+}
+
+fn parse_size(values: &[&str]) -> Result<Size<Option<Dimension>>, String> {
+    let [width, height] = parse_pair(values, parse_dimension)?;
+    Ok(Size {
+        width: Some(width),
+        height: Some(height),
+    })
+}
+
+// Top, right, bottom and left, with missing values repeated like in CSS.
+fn parse_edges<T>(values: &[&str], parse: fn(&str) -> Result<T, String>) -> Result<Rect<T>, String>
+where
+    T: Copy,
+{
+    let values = values
+        .iter()
+        .map(|value| parse(value))
+        .collect::<Result<Vec<T>, String>>()?;
+    let (top, right, bottom, left) = match values.as_slice() {
+        [all] => (*all, *all, *all, *all),
+        [vertical, horizontal] => (*vertical, *horizontal, *vertical, *horizontal),
+        [top, horizontal, bottom] => (*top, *horizontal, *bottom, *horizontal),
+        [top, right, bottom, left] => (*top, *right, *bottom, *left),
+        _ => return Err(format!("expected 1 to 4 values, found {}", values.len())),
+    };
+    Ok(Rect {
+        left,
+        right,
+        top,
+        bottom,
+    })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+struct Parser {
+    // The source with comments blanked out, keeping line breaks.
+    source: String,
+    rules: Vec<Rule>,
+}
+
+impl Parser {
+    fn new(source: &str) -> Parser {
+        let mut stripped = String::with_capacity(source.len());
+        let mut rest = source;
+        while let Some(start) = rest.find("/*") {
+            stripped.push_str(&rest[..start]);
+            let comment = &rest[start..];
+            let end = comment.find("*/").map_or(comment.len(), |end| end + 2);
+            stripped.extend(
+                comment[..end]
+                    .chars()
+                    .map(|c| if c == '\n' { '\n' } else { ' ' }),
+            );
+            rest = &comment[end..];
+        }
+        stripped.push_str(rest);
+        Parser {
+            source: stripped,
+            rules: Vec::new(),
+        }
+    }
+    fn line_at(&self, offset: usize) -> usize {
+        self.source[..offset].matches('\n').count() + 1
+    }
+    fn error(&self, offset: usize, message: impl Into<String>) -> StyleSheetError {
+        StyleSheetError::Parse {
+            line: self.line_at(offset),
+            message: message.into(),
+        }
+    }
+
+    fn parse(mut self) -> Result<StyleSheet, StyleSheetError> {
+        let mut offset = 0;
+        while let Some(skip) = self.source[offset..].find(|c: char| !c.is_whitespace()) {
+            let start = offset + skip;
+            let Some(open) = self.source[start..].find('{').map(|i| start + i) else {
+                return Err(self.error(start, "expected '{' after selector"));
+            };
+            let Some(close) = self.source[open..].find('}').map(|i| open + i) else {
+                return Err(self.error(open, "unclosed '{'"));
+            };
+            if let Some(nested) = self.source[open + 1..close].find('{') {
+                return Err(self.error(open + 1 + nested, "unexpected '{'"));
+            }
+            let selectors = self.parse_selectors(start, open)?;
+            let declarations = self.parse_declarations(open + 1, close)?;
+            for selector in selectors {
+                if selector.state.is_some() {
+                    if let Some(declaration) = declarations
+                        .iter()
+                        .find(|declaration| !declaration.properties.iter().all(Property::is_color))
+                    {
+                        return Err(self.error(
+                            start,
+                            format!(
+                                "{:?} can't be set for a state, in {:?}",
+                                declaration.name, selector.text
+                            ),
+                        ));
+                    }
+                }
+                self.rules.push(Rule {
+                    selector,
+                    declarations: declarations.clone(),
+                    line: self.line_at(start),
+                    order: self.rules.len(),
+                });
+            }
+            offset = close + 1;
+        }
+        Ok(StyleSheet { rules: self.rules })
+    }
+
+    fn parse_selectors(&self, start: usize, end: usize) -> Result<Vec<Selector>, StyleSheetError> {
+        self.source[start..end]
+            .split(',')
+            .map(|text| {
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                parse_selector(&text).map_err(|message| self.error(start, message))
+            })
+            .collect()
+    }
+
+    fn parse_declarations(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<Vec<Declaration>, StyleSheetError> {
+        let mut declarations = Vec::new();
+        let mut offset = start;
+        for text in self.source[start..end].split(';') {
+            let text_offset = offset + (text.len() - text.trim_start().len());
+            offset += text.len() + 1;
+            if text.trim().is_empty() {
+                continue;
+            }
+            let Some((name, value)) = text.split_once(':') else {
+                return Err(self.error(text_offset, format!("expected ':' in {:?}", text.trim())));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            let properties =
+                Property::parse(name, value).map_err(|message| self.error(text_offset, message))?;
+            declarations.push(Declaration {
+                name: name.to_string(),
+                value: value.to_string(),
+                properties,
+            });
+        }
+        Ok(declarations)
+    }
+}
+
+fn parse_selector(text: &str) -> Result<Selector, String> {
+    if text.is_empty() {
+        return Err("empty selector".to_string());
+    }
+    let mut compounds = Vec::new();
+    let mut state = None;
+    for part in text.split(' ') {
+        if state.is_some() {
+            return Err(format!("pseudo-classes must come last, in {text:?}"));
+        }
+        let (compound, part_state) = parse_compound(part)?;
+        compounds.push(compound);
+        state = part_state;
+    }
+    let subject = compounds.pop().unwrap();
+    Ok(Selector {
+        text: text.to_string(),
+        ancestors: compounds,
+        subject,
+        state,
+    })
+}
+
+fn parse_compound(text: &str) -> Result<(Compound, Option<StateClass>), String> {
+    let mut compound = Compound::default();
+    let mut state = None;
+    let mut rest = text;
+    if let Some(after) = rest.strip_prefix('*') {
+        rest = after;
+    }
+    while !rest.is_empty() {
+        let prefix = rest.chars().next().unwrap();
+        let body = if is_ident_char(prefix) {
+            rest
+        } else {
+            &rest[prefix.len_utf8()..]
+        };
+        let len = body.find(|c| !is_ident_char(c)).unwrap_or(body.len());
+        let name = &body[..len];
+        if name.is_empty() {
+            return Err(format!("invalid selector {text:?}"));
+        }
+        match prefix {
+            '.' => compound.classes.push(name.to_string()),
+            '#' if compound.id.is_none() => compound.id = Some(name.to_string()),
+            ':' if state.is_none() => {
+                state = Some(match name {
+                    "hover" => StateClass::Hover,
+                    "pressed" | "active" => StateClass::Pressed,
+                    "focused" | "focus" => StateClass::Focused,
+                    "disabled" => StateClass::Disabled,
+                    "checked" => StateClass::Checked,
+                    _ => return Err(format!("unknown pseudo-class {name:?}")),
+                })
+            }
+            _ if is_ident_char(prefix) && rest.len() == text.len() => {
+                compound.type_name = Some(name.to_string())
+            }
+            _ => return Err(format!("invalid selector {text:?}")),
+        }
+        rest = &body[len..];
+        if state.is_some() && !rest.is_empty() {
+            return Err(format!("pseudo-classes must come last, in {text:?}"));
+        }
+    }
+    Ok((compound, state))
+}
+
+impl Gui {
+    /// Installs `sheet` and applies it to every widget. `None` removes what the previous sheet
+    /// applied.
+    pub fn set_style_sheet(&self, sheet: Option<StyleSheet>) {
+        *self.style_sheet.borrow_mut() = sheet.map(Rc::new);
+        self.restyle(&self.root());
+    }
+    pub fn style_sheet(&self) -> Option<Rc<StyleSheet>> {
+        self.style_sheet.borrow().clone()
+    }
+    /// The rules of the installed style sheet that match `widget`, from the lowest precedence
+    /// to the highest, for debugging.
+    pub fn matched_rules(&self, widget: &Widget) -> Vec<Rule> {
+        self.style_sheet()
+            .map(|sheet| sheet.matching_rules(widget).into_iter().cloned().collect())
+            .unwrap_or_default()
+    }
+    // Matches `widget` and its descendants against the style sheet again, after the sheet or
+    // something selectors look at changed.
+    pub(crate) fn restyle(&self, widget: &Widget) {
+        let mut stack = vec![widget.clone()];
+        while let Some(widget) = stack.pop() {
+            widget.update_style();
+            stack.extend(widget.children().iter().cloned());
+        }
+    }
+}
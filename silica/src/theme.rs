@@ -0,0 +1,239 @@
+use std::{collections::BTreeMap, fmt, io, path::Path, rc::Rc};
+
+use serde::Deserialize;
+use taffy::prelude::*;
+
+use crate::{signal, Color, Connection, Gui, ParseColorError, ThemeColor};
+
+/// Colors, metrics and fonts shared by the built-in widgets. Install one with
+/// [`Gui::set_theme`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    colors: [Color; ThemeColor::ALL.len()],
+    /// Border width of buttons, checkboxes, focus rings and tooltips.
+    pub border_width: f32,
+    /// Border width of a toggle button that is on.
+    pub toggled_border_width: f32,
+    pub corner_radius: f32,
+    /// Space between the edge of a button or tooltip and its text.
+    pub padding: f32,
+    pub font_id: usize,
+    pub font_size: f32,
+    pub tooltip_font_size: f32,
+    pub scrollbar_width: f32,
+    pub button_min_size: Size<f32>,
+    pub checkbox_min_size: Size<f32>,
+    pub rocker_min_size: Size<f32>,
+}
+
+impl Theme {
+    fn with_grays(grays: [(ThemeColor, f32); 8]) -> Theme {
+        let mut theme = Theme {
+            colors: [Color::BLACK; ThemeColor::ALL.len()],
+            border_width: 1.0,
+            toggled_border_width: 3.0,
+            corner_radius: 3.0,
+            padding: 4.0,
+            font_id: 0,
+            font_size: 14.0,
+            tooltip_font_size: 12.0,
+            scrollbar_width: 10.0,
+            button_min_size: Size {
+                width: 128.0,
+                height: 32.0,
+            },
+            checkbox_min_size: Size {
+                width: 24.0,
+                height: 24.0,
+            },
+            rocker_min_size: Size {
+                width: 48.0,
+                height: 24.0,
+            },
+        };
+        for (role, value) in grays {
+            theme.set_color(role, Color::rgb(value, value, value));
+        }
+        theme
+    }
+    /// The default theme, dark text on light gray.
+    pub fn light() -> Theme {
+        Theme::with_grays([
+            (ThemeColor::Background, 0.847),
+            (ThemeColor::ButtonNormal, 0.784),
+            (ThemeColor::ButtonDisable, 0.816),
+            (ThemeColor::ButtonOver, 0.722),
+            (ThemeColor::ButtonPress, 0.659),
+            (ThemeColor::Border, 0.094),
+            (ThemeColor::Foreground, 0.094),
+            (ThemeColor::ForegroundDisable, 0.502),
+        ])
+    }
+    /// Light text on dark gray.
+    pub fn dark() -> Theme {
+        Theme::with_grays([
+            (ThemeColor::Background, 0.165),
+            (ThemeColor::ButtonNormal, 0.251),
+            (ThemeColor::ButtonDisable, 0.208),
+            (ThemeColor::ButtonOver, 0.314),
+            (ThemeColor::ButtonPress, 0.376),
+            (ThemeColor::Border, 0.612),
+            (ThemeColor::Foreground, 0.906),
+            (ThemeColor::ForegroundDisable, 0.471),
+        ])
+    }
+    /// White on black with thicker borders.
+    pub fn high_contrast() -> Theme {
+        let mut theme = Theme::with_grays([
+            (ThemeColor::Background, 0.0),
+            (ThemeColor::ButtonNormal, 0.0),
+            (ThemeColor::ButtonDisable, 0.0),
+            (ThemeColor::ButtonOver, 0.251),
+            (ThemeColor::ButtonPress, 0.439),
+            (ThemeColor::Border, 1.0),
+            (ThemeColor::Foreground, 1.0),
+            (ThemeColor::ForegroundDisable, 0.6),
+        ]);
+        theme.border_width = 2.0;
+        theme.toggled_border_width = 4.0;
+        theme.corner_radius = 0.0;
+        theme
+    }
+
+    /// Parses a theme from TOML. Every key is optional and defaults to the theme named by
+    /// `base` (`"light"`, `"dark"` or `"high-contrast"`), or to [`light`](Self::light):
+    ///
+    /// ```toml
+    /// base = "dark"
+    /// border_width = 2.0
+    /// button_min_size = [96.0, 28.0]
+    ///
+    /// [colors]
+    /// button_over = "#3d5a80"
+    /// foreground = "white"
+    /// ```
+    pub fn from_toml(source: &str) -> Result<Theme, ThemeError> {
+        let file: ThemeFile = toml::from_str(source).map_err(ThemeError::Toml)?;
+        file.into_theme()
+    }
+    /// Reads a theme from a TOML file, see [`from_toml`](Self::from_toml).
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeError> {
+        let source = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+        Theme::from_toml(&source)
+    }
+
+    pub fn color(&self, role: ThemeColor) -> Color {
+        self.colors[role as usize]
+    }
+    pub fn set_color(&mut self, role: ThemeColor, color: Color) {
+        self.colors[role as usize] = color;
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
+
+/// The error returned when a theme file can't be loaded.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    UnknownBase(String),
+    UnknownColor(String),
+    Color(ParseColorError),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "failed to read theme: {err}"),
+            ThemeError::Toml(err) => write!(f, "invalid theme: {err}"),
+            ThemeError::UnknownBase(name) => write!(f, "unknown base theme: {name:?}"),
+            ThemeError::UnknownColor(name) => write!(f, "unknown theme color: {name:?}"),
+            ThemeError::Color(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(err) => Some(err),
+            ThemeError::Toml(err) => Some(err),
+            ThemeError::Color(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    border_width: Option<f32>,
+    toggled_border_width: Option<f32>,
+    corner_radius: Option<f32>,
+    padding: Option<f32>,
+    font_id: Option<usize>,
+    font_size: Option<f32>,
+    tooltip_font_size: Option<f32>,
+    scrollbar_width: Option<f32>,
+    button_min_size: Option<[f32; 2]>,
+    checkbox_min_size: Option<[f32; 2]>,
+    rocker_min_size: Option<[f32; 2]>,
+    #[serde(default)]
+    colors: BTreeMap<String, String>,
+}
+
+impl ThemeFile {
+    fn into_theme(self) -> Result<Theme, ThemeError> {
+        let mut theme = match self.base.as_deref() {
+            None | Some("light") => Theme::light(),
+            Some("dark") => Theme::dark(),
+            Some("high-contrast") => Theme::high_contrast(),
+            Some(name) => return Err(ThemeError::UnknownBase(name.to_string())),
+        };
+        let size = |[width, height]: [f32; 2]| Size { width, height };
+        theme.border_width = self.border_width.unwrap_or(theme.border_width);
+        theme.toggled_border_width = self
+            .toggled_border_width
+            .unwrap_or(theme.toggled_border_width);
+        theme.corner_radius = self.corner_radius.unwrap_or(theme.corner_radius);
+        theme.padding = self.padding.unwrap_or(theme.padding);
+        theme.font_id = self.font_id.unwrap_or(theme.font_id);
+        theme.font_size = self.font_size.unwrap_or(theme.font_size);
+        theme.tooltip_font_size = self.tooltip_font_size.unwrap_or(theme.tooltip_font_size);
+        theme.scrollbar_width = self.scrollbar_width.unwrap_or(theme.scrollbar_width);
+        theme.button_min_size = self.button_min_size.map_or(theme.button_min_size, size);
+        theme.checkbox_min_size = self.checkbox_min_size.map_or(theme.checkbox_min_size, size);
+        theme.rocker_min_size = self.rocker_min_size.map_or(theme.rocker_min_size, size);
+        for (name, value) in self.colors {
+            let role = ThemeColor::from_name(&name).ok_or(ThemeError::UnknownColor(name))?;
+            theme.set_color(role, value.parse().map_err(ThemeError::Color)?);
+        }
+        Ok(theme)
+    }
+}
+
+impl Gui {
+    /// The theme that [`ThemeColor`] roles and widget defaults are looked up in.
+    pub fn theme(&self) -> Rc<Theme> {
+        self.theme.borrow().clone()
+    }
+    /// Replaces the active theme, then relayouts and repaints everything.
+    pub fn set_theme(self: &Rc<Self>, theme: Theme) {
+        *self.theme.borrow_mut() = Rc::new(theme);
+        self.signals.emit(self.clone(), signal::ThemeChanged);
+        self.mark_needs_layout();
+        self.damage_all();
+    }
+    pub fn connect_theme_changed<F>(&self, handler: F) -> Connection
+    where
+        F: FnMut(Rc<Self>, signal::ThemeChanged) + 'static,
+    {
+        self.signals.connect(handler)
+    }
+}
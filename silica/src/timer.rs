@@ -0,0 +1,204 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::Gui;
+
+/// The source of time for a [`Gui`]'s timers.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Reads the system's monotonic clock. This is the default clock, and the one backends expect
+/// when they wait for [`Gui::next_deadline`].
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, for testing timers without sleeping.
+pub struct MockClock {
+    now: Cell<Instant>,
+}
+
+impl MockClock {
+    pub fn new() -> Rc<Self> {
+        Rc::new(MockClock {
+            now: Cell::new(Instant::now()),
+        })
+    }
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+type TimerCallback = Rc<RefCell<dyn FnMut(Rc<Gui>)>>;
+
+struct Timer {
+    id: u64,
+    deadline: Instant,
+    period: Option<Duration>,
+    callback: TimerCallback,
+}
+
+#[derive(Default)]
+pub(crate) struct Timers {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    fn remove(&mut self, id: u64) -> Option<Timer> {
+        let index = self.timers.iter().position(|timer| timer.id == id)?;
+        Some(self.timers.swap_remove(index))
+    }
+}
+
+/// A handle to a timer added with [`Gui::add_timeout`] or [`Gui::add_interval`]. Dropping the
+/// handle does not cancel the timer.
+pub struct TimerHandle {
+    timers: Weak<RefCell<Timers>>,
+    id: u64,
+}
+
+impl TimerHandle {
+    /// Cancels the timer. Returns `false` if it had already fired or been cancelled.
+    pub fn cancel(&self) -> bool {
+        // The callback may own captures with their own `Drop`, so drop it after the borrow ends.
+        let removed = self
+            .timers
+            .upgrade()
+            .and_then(|timers| timers.borrow_mut().remove(self.id));
+        removed.is_some()
+    }
+    /// Whether the timer will still fire.
+    pub fn is_pending(&self) -> bool {
+        self.timers
+            .upgrade()
+            .map(|timers| {
+                timers
+                    .borrow()
+                    .timers
+                    .iter()
+                    .any(|timer| timer.id == self.id)
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl Gui {
+    /// Replaces the clock used by timers, usually with a [`MockClock`] in tests.
+    pub fn set_clock(&self, clock: Rc<dyn Clock>) {
+        *self.clock.borrow_mut() = clock;
+    }
+    pub fn now(&self) -> Instant {
+        self.clock.borrow().now()
+    }
+
+    fn add_timer(
+        &self,
+        duration: Duration,
+        period: Option<Duration>,
+        callback: TimerCallback,
+    ) -> TimerHandle {
+        let deadline = self.now() + duration;
+        let mut timers = self.timers.borrow_mut();
+        let id = timers.next_id;
+        timers.next_id += 1;
+        timers.timers.push(Timer {
+            id,
+            deadline,
+            period,
+            callback,
+        });
+        TimerHandle {
+            timers: Rc::downgrade(&self.timers),
+            id,
+        }
+    }
+    /// Calls `callback` once `duration` has passed. Timers are fired by the backend through
+    /// [`emit_timers`](Self::emit_timers).
+    pub fn add_timeout<F>(&self, duration: Duration, callback: F) -> TimerHandle
+    where
+        F: FnOnce(Rc<Gui>) + 'static,
+    {
+        let mut callback = Some(callback);
+        self.add_timer(
+            duration,
+            None,
+            Rc::new(RefCell::new(move |gui| {
+                if let Some(callback) = callback.take() {
+                    callback(gui);
+                }
+            })),
+        )
+    }
+    /// Calls `callback` every `period` until the returned handle is cancelled. If the backend
+    /// falls behind, missed calls are skipped rather than run in a burst.
+    pub fn add_interval<F>(&self, period: Duration, callback: F) -> TimerHandle
+    where
+        F: FnMut(Rc<Gui>) + 'static,
+    {
+        self.add_timer(period, Some(period), Rc::new(RefCell::new(callback)))
+    }
+    /// The earliest time a timer is due, for the backend to wait until.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers
+            .borrow()
+            .timers
+            .iter()
+            .map(|timer| timer.deadline)
+            .min()
+    }
+    /// Fires the timers that are due, in deadline order. Timers added or rescheduled by the
+    /// callbacks wait for the next call, even if they are already due.
+    pub fn emit_timers(self: &Rc<Self>) {
+        let now = self.now();
+        let mut due: Vec<u64> = self
+            .timers
+            .borrow()
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now)
+            .map(|timer| timer.id)
+            .collect();
+        loop {
+            // Timers are looked up one at a time, so that callbacks can cancel later timers.
+            let callback = {
+                let mut timers = self.timers.borrow_mut();
+                let Some(timer) = timers
+                    .timers
+                    .iter_mut()
+                    .filter(|timer| due.contains(&timer.id))
+                    .min_by_key(|timer| (timer.deadline, timer.id))
+                else {
+                    break;
+                };
+                let id = timer.id;
+                due.retain(|&due_id| due_id != id);
+                match timer.period {
+                    Some(period) => {
+                        timer.deadline += period;
+                        if timer.deadline <= now {
+                            timer.deadline = now + period;
+                        }
+                        timer.callback.clone()
+                    }
+                    None => timers.remove(id).unwrap().callback,
+                }
+            };
+            (callback.borrow_mut())(self.clone());
+        }
+    }
+}
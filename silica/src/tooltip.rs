@@ -0,0 +1,167 @@
+use std::{rc::Rc, time::Duration};
+
+use taffy::{geometry::Point, prelude::*};
+
+use crate::{
+    same_widget, DamageRect, DamageRegion, GraphicsContext, Gui, HorizontalAlign, TextSection,
+    ThemeColor, TimerHandle, Widget,
+};
+
+const OFFSET: Point<f32> = Point { x: 12.0, y: 18.0 };
+// There is no text measurement outside of drawing, so the box is sized from an average glyph
+// width, as a fraction of the font size, and the text is centered in it.
+const AVERAGE_CHAR_WIDTH: f32 = 0.6;
+
+struct ShownTooltip {
+    text: String,
+    rect: DamageRect,
+}
+
+pub(crate) struct TooltipState {
+    delay: Duration,
+    timeout: Duration,
+    // The widget under the pointer that has a tooltip.
+    widget: Option<Widget>,
+    shown: Option<ShownTooltip>,
+    // The pending show or hide timer.
+    timer: Option<TimerHandle>,
+    // Set after a press or a timeout, until the pointer moves to another widget.
+    suppressed: bool,
+}
+
+impl Default for TooltipState {
+    fn default() -> Self {
+        TooltipState {
+            delay: Duration::from_millis(500),
+            timeout: Duration::from_secs(5),
+            widget: None,
+            shown: None,
+            timer: None,
+            suppressed: false,
+        }
+    }
+}
+
+impl Gui {
+    /// Sets how long the pointer must rest over a widget before its tooltip appears.
+    pub fn set_tooltip_delay(&self, delay: Duration) {
+        self.tooltip.borrow_mut().delay = delay;
+    }
+    /// Sets how long a tooltip stays visible if the pointer doesn't leave the widget.
+    pub fn set_tooltip_timeout(&self, timeout: Duration) {
+        self.tooltip.borrow_mut().timeout = timeout;
+    }
+    /// The text of the tooltip currently shown, if any.
+    pub fn shown_tooltip(&self) -> Option<String> {
+        self.tooltip
+            .borrow()
+            .shown
+            .as_ref()
+            .map(|shown| shown.text.clone())
+    }
+
+    // Called after pointer motion, once `GuiState::hover` is up to date.
+    pub(crate) fn update_tooltip(self: &Rc<Self>) {
+        let hover = self.state.borrow().hover.clone();
+        let mut tooltip = self.tooltip.borrow_mut();
+        if !same_widget(hover.as_ref(), tooltip.widget.as_ref()) {
+            self.hide_tooltip(&mut tooltip);
+            tooltip.widget = hover;
+            tooltip.suppressed = false;
+        } else if tooltip.shown.is_some() || tooltip.suppressed {
+            return;
+        }
+        // The pointer moved, so the delay starts over.
+        if let Some(timer) = tooltip.timer.take() {
+            timer.cancel();
+        }
+        if tooltip.widget.is_some() {
+            tooltip.timer = Some(self.add_timeout(tooltip.delay, |gui| gui.show_tooltip()));
+        }
+    }
+    // Hides the tooltip until the pointer moves to another widget.
+    pub(crate) fn suppress_tooltip(&self) {
+        let mut tooltip = self.tooltip.borrow_mut();
+        self.hide_tooltip(&mut tooltip);
+        tooltip.suppressed = true;
+    }
+    fn hide_tooltip(&self, tooltip: &mut TooltipState) {
+        if let Some(timer) = tooltip.timer.take() {
+            timer.cancel();
+        }
+        if let Some(shown) = tooltip.shown.take() {
+            self.damage(shown.rect);
+        }
+    }
+    fn show_tooltip(&self) {
+        let mut tooltip = self.tooltip.borrow_mut();
+        tooltip.timer = None;
+        let Some(text) = tooltip.widget.as_ref().and_then(|widget| widget.tooltip()) else {
+            return;
+        };
+        let Some(pointer) = self.pointer_position() else {
+            return;
+        };
+        let theme = self.theme();
+        let font_size = theme.tooltip_font_size;
+        let size = Size {
+            width: text.chars().count() as f32 * AVERAGE_CHAR_WIDTH * font_size
+                + 2.0 * theme.padding,
+            height: font_size * 1.5 + 2.0 * theme.padding,
+        };
+        // Keep the tooltip inside the window.
+        let window = self.window_rect();
+        let origin = Point {
+            x: (pointer.x + OFFSET.x)
+                .min(window.right() - size.width)
+                .max(0.0),
+            y: (pointer.y + OFFSET.y)
+                .min(window.bottom() - size.height)
+                .max(0.0),
+        };
+        let rect = DamageRect::new(origin, size);
+        self.damage(rect);
+        tooltip.shown = Some(ShownTooltip { text, rect });
+
+        tooltip.timer = Some(self.add_timeout(tooltip.timeout, |gui| gui.suppress_tooltip()));
+    }
+
+    // Draws the tooltip above everything else.
+    pub(crate) fn draw_tooltip(
+        &self,
+        context: &mut dyn GraphicsContext,
+        region: Option<&DamageRegion>,
+    ) {
+        let tooltip = self.tooltip.borrow();
+        let Some(shown) = tooltip.shown.as_ref() else {
+            return;
+        };
+        if let Some(region) = region {
+            if !region.intersects(&shown.rect) {
+                return;
+            }
+        }
+        let theme = self.theme();
+        let size = shown.rect.size;
+        context.save();
+        context.translate(shown.rect.origin.x, shown.rect.origin.y);
+        context.clip_rounded_rect(Point::ZERO, size, theme.corner_radius);
+        context.set_color(ThemeColor::Background.into());
+        context.draw_rect(Point::ZERO, size);
+        context.set_color(ThemeColor::Border.into());
+        context.draw_border(size, Rect::points(theme.border_width));
+        context.set_color(ThemeColor::Foreground.into());
+        context.draw_text(
+            Point::ZERO,
+            size,
+            &TextSection {
+                font_id: theme.font_id,
+                font_size: theme.tooltip_font_size,
+                text: shown.text.clone(),
+                h_align: HorizontalAlign::Center,
+                ..Default::default()
+            },
+        );
+        context.restore();
+    }
+}
@@ -1,23 +1,41 @@
-use std::{cell::Cell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
 
 use taffy::{geometry::Point, prelude::*};
 
 use crate::{
-    define_widget, model::Model, signal, widget::Label, GraphicsContext, Gui, HorizontalAlign,
-    PointerState, Signals, ThemeColor, VerticalAlign, VisualStyle, WidgetData, WidgetObject,
+    define_widget,
+    model::Model,
+    signal,
+    signal::{Connection, ScopedConnection},
+    widget::Label,
+    Event, GraphicsContext, Gui, HorizontalAlign, PointerState, SignalFuture, Signals, Theme,
+    VerticalAlign, VisualStyle, WidgetData, WidgetObject,
 };
 
+/// The layout metrics a button takes from the theme: its minimum size and padding.
+type ThemeMetrics = (Size<Dimension>, Rect<LengthPercentage>);
+
 pub struct BaseButtonData {
     was_pressed: Cell<bool>,
+    // The metrics last taken from the theme, and the handler that updates them.
+    theme_metrics: Cell<Option<ThemeMetrics>>,
+    theme_changed: RefCell<Option<ScopedConnection>>,
+    model_changed: RefCell<Option<ScopedConnection>>,
 }
 
 impl BaseButtonData {
     fn new() -> Self {
         BaseButtonData {
             was_pressed: Cell::new(false),
+            theme_metrics: Cell::new(None),
+            theme_changed: RefCell::new(None),
+            model_changed: RefCell::new(None),
         }
     }
-    fn set_pointer_state<F>(&self, state: PointerState, on_activate: F) -> VisualStyle
+    fn set_pointer_state<F>(&self, state: PointerState, on_activate: F)
     where
         F: FnOnce(),
     {
@@ -25,14 +43,84 @@ impl BaseButtonData {
             on_activate();
         }
         self.was_pressed.set(state == PointerState::Press);
-
-        let mut visual = VisualStyle::BUTTON;
-        match state {
-            PointerState::None => visual.background = Some(ThemeColor::ButtonNormal),
-            PointerState::Over => visual.background = Some(ThemeColor::ButtonOver),
-            PointerState::Press => visual.background = Some(ThemeColor::ButtonPress),
+    }
+    /// Uses the theme's metrics for the widget's layout, now and whenever the theme changes.
+    /// They are defaults: a minimum size or padding that was changed through `set_layout` is
+    /// kept.
+    fn bind_theme_layout<T>(
+        data: &Rc<WidgetData<T>>,
+        base: fn(&WidgetData<T>) -> &BaseButtonData,
+        metrics: fn(&Theme) -> ThemeMetrics,
+    ) where
+        T: WidgetObject + 'static,
+    {
+        let apply = move |data: &WidgetData<T>, theme: &Theme| {
+            let (min_size, padding) = metrics(theme);
+            let old = base(data).theme_metrics.replace(Some((min_size, padding)));
+            let mut layout = data.layout();
+            let old_layout = layout.clone();
+            if old.is_none_or(|(old, _)| layout.min_size == old) {
+                layout.min_size = min_size;
+            }
+            if old.is_none_or(|(_, old)| layout.padding == old) {
+                layout.padding = padding;
+            }
+            if layout != old_layout {
+                data.set_layout(layout);
+            }
+        };
+        apply(data, &data.gui().theme());
+        let weak: Weak<WidgetData<T>> = Rc::downgrade(data);
+        let connection = data.gui().connect_theme_changed(move |gui, _| {
+            if let Some(data) = weak.upgrade() {
+                apply(&data, &gui.theme());
+            }
+        });
+        *base(data).theme_changed.borrow_mut() = Some(connection.scoped());
+    }
+    /// Repaints the widget whenever `model` changes, including through another widget or code
+    /// that sets it directly.
+    fn bind_model<T>(
+        data: &Rc<WidgetData<T>>,
+        base: fn(&WidgetData<T>) -> &BaseButtonData,
+        model: &Rc<dyn Model<bool>>,
+    ) where
+        T: WidgetObject + 'static,
+    {
+        let weak: Weak<WidgetData<T>> = Rc::downgrade(data);
+        let connection = model.connect_changed(Box::new(move || {
+            if let Some(data) = weak.upgrade() {
+                data.queue_draw();
+            }
+        }));
+        *base(data).model_changed.borrow_mut() = Some(connection.scoped());
+    }
+    fn draw_focus<T>(data: &WidgetData<T>, context: &mut dyn GraphicsContext, size: Size<f32>) {
+        if data.has_focus() {
+            context.save();
+            context.translate(3.0, 3.0);
+            context.draw_border(
+                size.map(|v| v - 6.0),
+                Rect::points(data.gui().theme().border_width),
+            );
+            context.restore();
         }
-        visual
+    }
+    /// Connects the Space/Enter key handler that calls `activate` on a focused button.
+    fn connect_keys<T, F>(data: &Rc<WidgetData<T>>, activate: F)
+    where
+        T: 'static,
+        F: Fn(Rc<WidgetData<T>>) + 'static,
+    {
+        let weak: Weak<WidgetData<T>> = Rc::downgrade(data);
+        data.connect_event(move |_, event: Event<signal::KeyPress>| {
+            if matches!(event.signal.key, signal::Key::Space | signal::Key::Enter) {
+                if let Some(data) = weak.upgrade() {
+                    event.stop_propagation();
+                    activate(data);
+                }
+            }
+        });
     }
 }
 
@@ -44,24 +132,32 @@ pub struct ButtonData {
 }
 
 impl WidgetObject for ButtonData {
+    fn type_name() -> &'static str {
+        "Button"
+    }
     fn draw(data: &WidgetData<Self>, context: &mut dyn GraphicsContext, size: Size<f32>) {
-        let mut border_width = 1.0;
+        let theme = data.gui().theme();
+        let mut border_width = theme.border_width;
         if let Some(model) = data.object.toggle.as_ref() {
             if model.get() {
-                border_width = 3.0;
+                border_width = theme.toggled_border_width;
             }
         }
         context.draw_border(size, Rect::points(border_width));
+        BaseButtonData::draw_focus(data, context, size);
     }
     fn set_pointer_state(data: Rc<WidgetData<Self>>, state: PointerState) {
         let button = Button(data);
-        let visual = button.object.base.set_pointer_state(state, || {
-            if let Some(model) = button.object.toggle.as_ref() {
-                model.clone().set(!model.get());
-            }
-            button.object.signals.emit(button.clone(), signal::Activate);
-        });
-        button.set_visual(Some(visual));
+        button
+            .object
+            .base
+            .set_pointer_state(state, || button.activate());
+    }
+    fn is_checked(data: &WidgetData<Self>) -> bool {
+        data.object.toggle.as_ref().is_some_and(|model| model.get())
+    }
+    fn can_focus(_data: &WidgetData<Self>) -> bool {
+        true
     }
 }
 
@@ -78,10 +174,6 @@ impl Button {
         });
 
         let style = Style {
-            min_size: Size {
-                width: Dimension::Points(128.),
-                height: Dimension::Points(32.),
-            },
             align_items: Some(AlignItems::Stretch),
             justify_items: Some(JustifyItems::Stretch),
             ..Default::default()
@@ -98,6 +190,20 @@ impl Button {
             },
         ));
         button.add_child(label);
+        BaseButtonData::bind_theme_layout(
+            &button.0,
+            |data| &data.object.base,
+            |theme| {
+                (
+                    theme.button_min_size.map(Dimension::Points),
+                    Rect::points(theme.padding),
+                )
+            },
+        );
+        BaseButtonData::connect_keys(&button.0, |data| Button(data).activate());
+        if let Some(toggle) = button.object.toggle.clone() {
+            BaseButtonData::bind_model(&button.0, |data| &data.object.base, &toggle);
+        }
         button
     }
     pub fn with_label(gui: Rc<Gui>, label_text: String) -> Self {
@@ -115,11 +221,26 @@ impl Button {
         self.object.label.clone()
     }
 
-    pub fn connect_activate<F>(&self, handler: F)
+    pub fn activate(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Some(model) = self.object.toggle.as_ref() {
+            model.clone().set(!model.get());
+        }
+        self.queue_draw();
+        self.object.signals.emit(self.clone(), signal::Activate);
+    }
+
+    pub fn connect_activate<F>(&self, handler: F) -> Connection
     where
         F: FnMut(Button, signal::Activate) + 'static,
     {
-        self.object.signals.connect(handler);
+        self.object.signals.connect(handler)
+    }
+    /// Resolves on the next activation, for use in async code.
+    pub fn activated(&self) -> SignalFuture<signal::Activate> {
+        self.object.signals.next()
     }
 }
 
@@ -130,8 +251,11 @@ pub struct CheckboxData {
 }
 
 impl WidgetObject for CheckboxData {
+    fn type_name() -> &'static str {
+        "Checkbox"
+    }
     fn draw(data: &WidgetData<Self>, context: &mut dyn GraphicsContext, size: Size<f32>) {
-        context.draw_border(size, Rect::points(1.0));
+        context.draw_border(size, Rect::points(data.gui().theme().border_width));
         if data.object.model.get() {
             if data.object.rocker {
                 let point = Point {
@@ -165,14 +289,20 @@ impl WidgetObject for CheckboxData {
                 },
             );
         }
+        BaseButtonData::draw_focus(data, context, size);
     }
     fn set_pointer_state(data: Rc<WidgetData<Self>>, state: PointerState) {
         let checkbox = Checkbox(data);
-        let visual = checkbox.object.base.set_pointer_state(state, || {
-            let model = &checkbox.object.model;
-            model.clone().set(!model.get());
-        });
-        checkbox.set_visual(Some(visual));
+        checkbox
+            .object
+            .base
+            .set_pointer_state(state, || checkbox.activate());
+    }
+    fn is_checked(data: &WidgetData<Self>) -> bool {
+        data.object.model.get()
+    }
+    fn can_focus(_data: &WidgetData<Self>) -> bool {
+        true
     }
 }
 
@@ -180,44 +310,47 @@ define_widget!(Checkbox, CheckboxData);
 
 impl Checkbox {
     pub fn new(gui: Rc<Gui>, model: Rc<dyn Model<bool>>) -> Self {
-        let style = Style {
-            min_size: Size {
-                width: Dimension::Points(24.),
-                height: Dimension::Points(24.),
-            },
-            ..Default::default()
-        };
-        Checkbox(WidgetData::with_style(
-            gui,
-            style,
-            Some(VisualStyle::BUTTON),
-            CheckboxData {
-                base: BaseButtonData::new(),
-                model,
-                rocker: false,
-            },
-        ))
+        let checkbox = Self::with_model(gui, model, false);
+        BaseButtonData::bind_theme_layout(
+            &checkbox.0,
+            |data| &data.object.base,
+            |theme| (theme.checkbox_min_size.map(Dimension::Points), Rect::zero()),
+        );
+        checkbox
     }
     pub fn new_rocker(gui: Rc<Gui>, model: Rc<dyn Model<bool>>) -> Self {
-        let style = Style {
-            min_size: Size {
-                width: Dimension::Points(48.),
-                height: Dimension::Points(24.),
-            },
-            ..Default::default()
-        };
-        Checkbox(WidgetData::with_style(
+        let checkbox = Self::with_model(gui, model, true);
+        BaseButtonData::bind_theme_layout(
+            &checkbox.0,
+            |data| &data.object.base,
+            |theme| (theme.rocker_min_size.map(Dimension::Points), Rect::zero()),
+        );
+        checkbox
+    }
+    fn with_model(gui: Rc<Gui>, model: Rc<dyn Model<bool>>, rocker: bool) -> Self {
+        let checkbox = Checkbox(WidgetData::with_style(
             gui,
-            style,
+            Style::default(),
             Some(VisualStyle::BUTTON),
             CheckboxData {
                 base: BaseButtonData::new(),
                 model,
-                rocker: true,
+                rocker,
             },
-        ))
+        ));
+        BaseButtonData::connect_keys(&checkbox.0, |data| Checkbox(data).activate());
+        let model = checkbox.object.model.clone();
+        BaseButtonData::bind_model(&checkbox.0, |data| &data.object.base, &model);
+        checkbox
     }
     pub fn value(&self) -> bool {
         self.object.model.get()
     }
+    pub fn activate(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let model = &self.object.model;
+        model.clone().set(!model.get());
+    }
 }
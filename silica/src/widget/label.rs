@@ -1,4 +1,7 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use taffy::geometry::{Point, Size};
 
@@ -9,11 +12,24 @@ use crate::{
 
 pub struct LabelData {
     text: RefCell<TextSection>,
+    // Set with `set_font` and `set_font_size`, otherwise the theme's font is used.
+    font_id: Cell<Option<usize>>,
+    font_size: Cell<Option<f32>>,
 }
 
 impl WidgetObject for LabelData {
+    fn type_name() -> &'static str {
+        "Label"
+    }
     fn draw(data: &WidgetData<Self>, context: &mut dyn GraphicsContext, size: Size<f32>) {
-        context.draw_text(Point::ZERO, size, &data.object.text.borrow());
+        let theme = data.gui().theme();
+        let mut text = data.object.text.borrow_mut();
+        text.font_id = data.object.font_id.get().unwrap_or(theme.font_id);
+        text.font_size = data.object.font_size.get().unwrap_or(theme.font_size);
+        context.save();
+        context.clip_rect(Point::ZERO, size);
+        context.draw_text(Point::ZERO, size, &text);
+        context.restore();
     }
 }
 
@@ -26,6 +42,8 @@ impl Label {
             true,
             LabelData {
                 text: RefCell::new(TextSection::default()),
+                font_id: Cell::new(None),
+                font_size: Cell::new(None),
             },
         ))
     }
@@ -41,21 +59,26 @@ impl Label {
     pub fn set_text(&self, string: String) {
         let mut text = self.object.text.borrow_mut();
         text.text = string;
+        self.queue_layout();
     }
+    /// Overrides the theme's font.
     pub fn set_font(&self, font_id: usize) {
-        let mut text = self.object.text.borrow_mut();
-        text.font_id = font_id;
+        self.object.font_id.set(Some(font_id));
+        self.queue_layout();
     }
+    /// Overrides the theme's font size.
     pub fn set_font_size(&self, font_size: f32) {
-        let mut text = self.object.text.borrow_mut();
-        text.font_size = font_size;
+        self.object.font_size.set(Some(font_size));
+        self.queue_layout();
     }
     pub fn set_halign(&self, h_align: HorizontalAlign) {
         let mut text = self.object.text.borrow_mut();
         text.h_align = h_align;
+        self.queue_draw();
     }
     pub fn set_valign(&self, v_align: VerticalAlign) {
         let mut text = self.object.text.borrow_mut();
         text.v_align = v_align;
+        self.queue_draw();
     }
 }
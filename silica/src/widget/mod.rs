@@ -1,8 +1,10 @@
 mod button;
 mod label;
+mod scroll_area;
 
 pub use button::{Button, Checkbox};
 pub use label::Label;
+pub use scroll_area::ScrollArea;
 
 use std::rc::Rc;
 use taffy::geometry::Size;
@@ -29,6 +31,9 @@ macro_rules! define_widget {
 }
 
 impl WidgetObject for () {
+    fn type_name() -> &'static str {
+        "Container"
+    }
     fn draw(_data: &WidgetData<Self>, _context: &mut dyn GraphicsContext, _size: Size<f32>) {}
 }
 
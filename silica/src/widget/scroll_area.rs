@@ -0,0 +1,389 @@
+use std::{cell::Cell, rc::Rc};
+
+use taffy::{geometry::Point, prelude::*};
+
+use crate::{
+    define_widget,
+    model::{FloatModel, Model},
+    signal,
+    widget::Container,
+    Event, GraphicsContext, Gui, Overflow, ThemeColor, VisualStyle, WidgetData, WidgetObject,
+};
+
+const MIN_THUMB_LENGTH: f32 = 16.0;
+const SCROLL_LINE: f32 = 40.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// Geometry of one scrollbar, along its axis.
+struct Scrollbar {
+    track: f32,
+    thumb_start: f32,
+    thumb_length: f32,
+    max_offset: f32,
+}
+
+impl Scrollbar {
+    fn offset_per_pixel(&self) -> f32 {
+        let travel = self.track - self.thumb_length;
+        if travel > 0.0 {
+            self.max_offset / travel
+        } else {
+            0.0
+        }
+    }
+}
+
+struct Drag {
+    axis: Axis,
+    start_pointer: f32,
+    start_offset: f32,
+}
+
+pub struct ScrollAreaData {
+    content: Container,
+    scroll_x: Rc<FloatModel>,
+    scroll_y: Rc<FloatModel>,
+    drag: Cell<Option<Drag>>,
+}
+
+impl ScrollAreaData {
+    fn model(&self, axis: Axis) -> &Rc<FloatModel> {
+        match axis {
+            Axis::Horizontal => &self.scroll_x,
+            Axis::Vertical => &self.scroll_y,
+        }
+    }
+}
+
+impl WidgetData<ScrollAreaData> {
+    /// How far the content can scroll: until its far edge meets the area's padding, or the
+    /// scrollbar that covers it.
+    fn max_offset(&self) -> Size<f32> {
+        let size = self.size();
+        let (location, content_size) = {
+            let layout_tree = self.gui.layout.borrow();
+            let layout = layout_tree.layout(self.object.content.node).unwrap();
+            (layout.location, layout.size)
+        };
+        let style = self.computed_layout();
+        let resolve = |value: LengthPercentage| match value {
+            LengthPercentage::Points(points) => points,
+            LengthPercentage::Percent(percent) => percent * size.width,
+        };
+        let view = Size {
+            width: size.width - resolve(style.padding.right) - resolve(style.border.right),
+            height: size.height - resolve(style.padding.bottom) - resolve(style.border.bottom),
+        };
+        let bar = self.scrollbar_width();
+        let overflow = |max: Size<f32>| {
+            let bar_width = if max.height > 0.0 { bar } else { 0.0 };
+            let bar_height = if max.width > 0.0 { bar } else { 0.0 };
+            Size {
+                width: (location.x + content_size.width - (view.width - bar_width)).max(0.0),
+                height: (location.y + content_size.height - (view.height - bar_height)).max(0.0),
+            }
+        };
+        // Each scrollbar takes space from the other axis, which can make it overflow as well.
+        let mut max = overflow(Size::ZERO);
+        for _ in 0..2 {
+            max = overflow(max);
+        }
+        max
+    }
+    fn offset(&self, axis: Axis) -> f32 {
+        let max_offset = self.max_offset();
+        let max = match axis {
+            Axis::Horizontal => max_offset.width,
+            Axis::Vertical => max_offset.height,
+        };
+        self.object.model(axis).get().clamp(0.0, max)
+    }
+    fn set_offset(&self, axis: Axis, offset: f32) {
+        let max_offset = self.max_offset();
+        let max = match axis {
+            Axis::Horizontal => max_offset.width,
+            Axis::Vertical => max_offset.height,
+        };
+        self.object.model(axis).clone().set(offset.clamp(0.0, max));
+    }
+    fn scrollbar_width(&self) -> f32 {
+        self.gui.theme().scrollbar_width
+    }
+    fn scrollbar(&self, axis: Axis) -> Option<Scrollbar> {
+        let size = self.size();
+        let max_offset = self.max_offset();
+        let (length, max, other_max) = match axis {
+            Axis::Horizontal => (size.width, max_offset.width, max_offset.height),
+            Axis::Vertical => (size.height, max_offset.height, max_offset.width),
+        };
+        if max <= 0.0 {
+            return None;
+        }
+        // Leave the corner free when both scrollbars are shown.
+        let track = if other_max > 0.0 {
+            length - self.scrollbar_width()
+        } else {
+            length
+        };
+        let thumb_length = (track * length / (length + max))
+            .max(MIN_THUMB_LENGTH)
+            .min(track);
+        let thumb_start = (track - thumb_length) * self.offset(axis) / max;
+        Some(Scrollbar {
+            track,
+            thumb_start,
+            thumb_length,
+            max_offset: max,
+        })
+    }
+    /// Returns the scrollbar under `point`, relative to the scroll area.
+    fn scrollbar_at(&self, point: Point<f32>) -> Option<(Axis, Scrollbar)> {
+        let size = self.size();
+        let width = self.scrollbar_width();
+        if point.x >= size.width - width {
+            if let Some(bar) = self.scrollbar(Axis::Vertical) {
+                if point.y < bar.track {
+                    return Some((Axis::Vertical, bar));
+                }
+            }
+        }
+        if point.y >= size.height - width {
+            if let Some(bar) = self.scrollbar(Axis::Horizontal) {
+                if point.x < bar.track {
+                    return Some((Axis::Horizontal, bar));
+                }
+            }
+        }
+        None
+    }
+    fn local_pointer(&self) -> Option<Point<f32>> {
+        let pointer = self.gui.pointer_position()?;
+        let position = self.window_position();
+        Some(Point {
+            x: pointer.x - position.x,
+            y: pointer.y - position.y,
+        })
+    }
+
+    fn on_press(&self) -> bool {
+        let Some(pointer) = self.local_pointer() else {
+            return false;
+        };
+        let Some((axis, bar)) = self.scrollbar_at(pointer) else {
+            return false;
+        };
+        let (along, page) = match axis {
+            Axis::Horizontal => (pointer.x, self.size().width),
+            Axis::Vertical => (pointer.y, self.size().height),
+        };
+        let offset = self.offset(axis);
+        if along < bar.thumb_start {
+            self.set_offset(axis, offset - page);
+        } else if along >= bar.thumb_start + bar.thumb_length {
+            self.set_offset(axis, offset + page);
+        } else {
+            self.object.drag.set(Some(Drag {
+                axis,
+                start_pointer: along,
+                start_offset: offset,
+            }));
+            self.grab_pointer();
+        }
+        true
+    }
+    fn on_motion(&self) -> bool {
+        let Some(drag) = self.object.drag.take() else {
+            return false;
+        };
+        if let (Some(pointer), Some(bar)) = (self.local_pointer(), self.scrollbar(drag.axis)) {
+            let along = match drag.axis {
+                Axis::Horizontal => pointer.x,
+                Axis::Vertical => pointer.y,
+            };
+            let offset = drag.start_offset + (along - drag.start_pointer) * bar.offset_per_pixel();
+            self.set_offset(drag.axis, offset);
+        }
+        self.object.drag.set(Some(drag));
+        true
+    }
+    fn on_release(&self) -> bool {
+        if self.object.drag.take().is_some() {
+            self.release_pointer();
+            true
+        } else {
+            false
+        }
+    }
+    fn on_scroll(&self, scroll: &signal::Scroll) -> bool {
+        let mut scrolled = false;
+        for (axis, delta) in [(Axis::Horizontal, scroll.dx), (Axis::Vertical, scroll.dy)] {
+            let offset = self.offset(axis);
+            self.set_offset(axis, offset + delta * SCROLL_LINE);
+            scrolled |= self.offset(axis) != offset;
+        }
+        scrolled
+    }
+}
+
+impl WidgetObject for ScrollAreaData {
+    fn type_name() -> &'static str {
+        "ScrollArea"
+    }
+    fn draw(_data: &WidgetData<Self>, _context: &mut dyn GraphicsContext, _size: Size<f32>) {}
+    fn draw_overlay(data: &WidgetData<Self>, context: &mut dyn GraphicsContext, size: Size<f32>) {
+        let width = data.scrollbar_width();
+        if let Some(bar) = data.scrollbar(Axis::Vertical) {
+            let x = size.width - width;
+            context.set_color(ThemeColor::ButtonNormal.into());
+            context.draw_rect(
+                Point { x, y: 0.0 },
+                Size {
+                    width,
+                    height: bar.track,
+                },
+            );
+            context.set_color(ThemeColor::ButtonPress.into());
+            context.draw_rect(
+                Point {
+                    x,
+                    y: bar.thumb_start,
+                },
+                Size {
+                    width,
+                    height: bar.thumb_length,
+                },
+            );
+        }
+        if let Some(bar) = data.scrollbar(Axis::Horizontal) {
+            let y = size.height - width;
+            context.set_color(ThemeColor::ButtonNormal.into());
+            context.draw_rect(
+                Point { x: 0.0, y },
+                Size {
+                    width: bar.track,
+                    height: width,
+                },
+            );
+            context.set_color(ThemeColor::ButtonPress.into());
+            context.draw_rect(
+                Point {
+                    x: bar.thumb_start,
+                    y,
+                },
+                Size {
+                    width: bar.thumb_length,
+                    height: width,
+                },
+            );
+        }
+    }
+    fn scroll_offset(data: &WidgetData<Self>) -> Point<f32> {
+        Point {
+            x: data.offset(Axis::Horizontal),
+            y: data.offset(Axis::Vertical),
+        }
+    }
+    fn hit_children(data: &WidgetData<Self>, point: Point<f32>) -> bool {
+        data.scrollbar_at(point).is_none()
+    }
+}
+
+define_widget!(ScrollArea, ScrollAreaData);
+
+impl ScrollArea {
+    pub fn new(gui: Rc<Gui>) -> Self {
+        Self::with_models(gui, FloatModel::new(0.0), FloatModel::new(0.0))
+    }
+    /// Creates a scroll area whose horizontal and vertical offsets, in pixels, are stored in the
+    /// given models.
+    pub fn with_models(gui: Rc<Gui>, scroll_x: Rc<FloatModel>, scroll_y: Rc<FloatModel>) -> Self {
+        let content = Container::new(gui.clone());
+        content.set_layout(Style {
+            flex_shrink: 0.0,
+            flex_direction: FlexDirection::Column,
+            ..Default::default()
+        });
+
+        let style = Style {
+            align_items: Some(AlignItems::Start),
+            min_size: Size {
+                width: Dimension::Points(0.0),
+                height: Dimension::Points(0.0),
+            },
+            ..Default::default()
+        };
+        let scroll_area = ScrollArea(WidgetData::with_style(
+            gui,
+            style,
+            Some(VisualStyle::default()),
+            ScrollAreaData {
+                content: content.clone(),
+                scroll_x: scroll_x.clone(),
+                scroll_y: scroll_y.clone(),
+                drag: Cell::new(None),
+            },
+        ));
+        scroll_area.set_overflow(Overflow::Clip);
+        scroll_area.add_child(content);
+
+        let weak = Rc::downgrade(&scroll_area.0);
+        for model in [scroll_x, scroll_y] {
+            let weak = weak.clone();
+            model.connect_change(move |_, signal::Change| {
+                if let Some(data) = weak.upgrade() {
+                    data.queue_draw();
+                }
+            });
+        }
+        scroll_area.connect_capture({
+            let weak = weak.clone();
+            move |_, event: Event<signal::PointerButton>| {
+                let Some(data) = weak.upgrade() else {
+                    return;
+                };
+                let handled = match event.signal {
+                    signal::PointerButton::Primary(true) => data.on_press(),
+                    signal::PointerButton::Primary(false) => data.on_release(),
+                    _ => false,
+                };
+                if handled {
+                    event.stop_propagation();
+                }
+            }
+        });
+        scroll_area.connect_capture({
+            let weak = weak.clone();
+            move |_, event: Event<signal::PointerMotion>| {
+                if weak.upgrade().map(|data| data.on_motion()).unwrap_or(false) {
+                    event.stop_propagation();
+                }
+            }
+        });
+        scroll_area.connect_event(move |_, event: Event<signal::Scroll>| {
+            if weak
+                .upgrade()
+                .map(|data| data.on_scroll(&event.signal))
+                .unwrap_or(false)
+            {
+                event.stop_propagation();
+            }
+        });
+        scroll_area
+    }
+
+    /// The container holding the scrolled content.
+    pub fn content(&self) -> Container {
+        self.object.content.clone()
+    }
+    pub fn scroll_x(&self) -> Rc<FloatModel> {
+        self.object.scroll_x.clone()
+    }
+    pub fn scroll_y(&self) -> Rc<FloatModel> {
+        self.object.scroll_y.clone()
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use silica::{
+    taffy::prelude::*, widget::Button, Color, Gui, HeadlessContext, MockClock, Theme, ThemeColor,
+    FRAME_INTERVAL,
+};
+
+#[test]
+fn hover_fade_follows_theme_switch() {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let gui = root.gui();
+    let clock = MockClock::new();
+    gui.set_clock(clock.clone());
+    let button = Button::with_label(gui.clone(), "Button".to_string());
+    root.add_child(button.clone());
+    gui.emit_layout(None);
+
+    gui.emit_pointer_motion(10.0, 10.0);
+    clock.advance(Duration::from_millis(48));
+    gui.emit_timers();
+
+    let red = Color::rgb(1.0, 0.0, 0.0);
+    let mut theme = Theme::light();
+    theme.set_color(ThemeColor::ButtonNormal, red);
+    theme.set_color(ThemeColor::ButtonOver, red);
+    gui.set_theme(theme);
+    clock.advance(FRAME_INTERVAL);
+    gui.emit_timers();
+    gui.emit_layout(None);
+
+    // Still fading, so the colors come from the transition rather than the hover state.
+    let mut context = HeadlessContext::new(200, 100, gui.theme());
+    gui.draw(&mut context, root);
+    assert_eq!(context.pixel(60, 20).to_rgba8(), red.to_rgba8());
+}
@@ -0,0 +1,231 @@
+use std::rc::Rc;
+
+use silica::{
+    border_dashes, border_quads, resolve_border, taffy::prelude::*, widget::Container, BorderStyle,
+    Color, CornerRadius, GraphicsContext, HeadlessContext, Theme, ThemeColor, VisualStyle,
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+fn context(width: u32, height: u32) -> HeadlessContext {
+    HeadlessContext::new(width, height, Rc::new(Theme::default()))
+}
+
+fn uniform(width: f32) -> Rect<f32> {
+    Rect {
+        left: width,
+        right: width,
+        top: width,
+        bottom: width,
+    }
+}
+
+fn pixel(context: &HeadlessContext, x: u32, y: u32) -> [u8; 4] {
+    context.pixel(x, y).to_rgba8()
+}
+
+fn draw_red_border(width: u32, height: u32, border: Rect<LengthPercentage>) -> HeadlessContext {
+    let mut context = context(width, height);
+    context.set_color(Color::rgb(1.0, 0.0, 0.0).into());
+    context.draw_border(
+        Size {
+            width: width as f32,
+            height: height as f32,
+        },
+        border,
+    );
+    context
+}
+
+#[test]
+fn uniform_border_covers_edges() {
+    let context = draw_red_border(10, 10, Rect::points(1.0));
+    for i in 0..10 {
+        assert_eq!(pixel(&context, i, 0), RED);
+        assert_eq!(pixel(&context, i, 9), RED);
+        assert_eq!(pixel(&context, 0, i), RED);
+        assert_eq!(pixel(&context, 9, i), RED);
+    }
+    for x in 1..9 {
+        for y in 1..9 {
+            assert_eq!(pixel(&context, x, y), CLEAR);
+        }
+    }
+}
+
+#[test]
+fn each_side_uses_its_own_width() {
+    let border = Rect {
+        left: LengthPercentage::Points(3.0),
+        right: LengthPercentage::Points(0.0),
+        top: LengthPercentage::Points(1.0),
+        bottom: LengthPercentage::Points(2.0),
+    };
+    let context = draw_red_border(10, 10, border);
+    assert_eq!(pixel(&context, 2, 5), RED);
+    assert_eq!(pixel(&context, 3, 5), CLEAR);
+    assert_eq!(pixel(&context, 9, 5), CLEAR);
+    assert_eq!(pixel(&context, 5, 0), RED);
+    assert_eq!(pixel(&context, 5, 1), CLEAR);
+    assert_eq!(pixel(&context, 5, 8), RED);
+    assert_eq!(pixel(&context, 5, 7), CLEAR);
+}
+
+#[test]
+fn percentages_resolve_against_size() {
+    let border = Rect {
+        left: LengthPercentage::Percent(0.2),
+        right: LengthPercentage::Points(0.0),
+        top: LengthPercentage::Percent(0.1),
+        bottom: LengthPercentage::Points(0.0),
+    };
+    let resolved = resolve_border(
+        Size {
+            width: 20.0,
+            height: 30.0,
+        },
+        border,
+    );
+    assert_eq!((resolved.left, resolved.top), (4.0, 3.0));
+    let context = draw_red_border(20, 30, border);
+    assert_eq!(pixel(&context, 3, 15), RED);
+    assert_eq!(pixel(&context, 4, 15), CLEAR);
+    assert_eq!(pixel(&context, 10, 2), RED);
+    assert_eq!(pixel(&context, 10, 3), CLEAR);
+}
+
+#[test]
+fn oversized_borders_are_scaled_to_fit() {
+    let size = Size {
+        width: 10.0,
+        height: 10.0,
+    };
+    let resolved = resolve_border(size, Rect::points(8.0));
+    assert_eq!(resolved, uniform(5.0));
+    let context = draw_red_border(10, 10, Rect::points(8.0));
+    for x in 0..10 {
+        for y in 0..10 {
+            assert_eq!(pixel(&context, x, y)[3], 255, "pixel {x}, {y}");
+        }
+    }
+}
+
+#[test]
+fn sides_meet_on_the_diagonal() {
+    let size = Size {
+        width: 10.0,
+        height: 10.0,
+    };
+    let quads = border_quads(size, uniform(4.0));
+    assert_eq!(quads.top[3], quads.left[2]);
+
+    let mut context = context(10, 10);
+    context.draw_border_sides(
+        size,
+        uniform(4.0),
+        Rect {
+            left: Some(Color::rgb(0.0, 0.0, 1.0).into()),
+            right: None,
+            top: Some(Color::rgb(1.0, 0.0, 0.0).into()),
+            bottom: None,
+        },
+        CornerRadius::ZERO,
+        BorderStyle::Solid,
+    );
+    assert_eq!(pixel(&context, 2, 0), RED);
+    assert_eq!(pixel(&context, 3, 1), RED);
+    assert_eq!(pixel(&context, 0, 2), BLUE);
+    assert_eq!(pixel(&context, 1, 3), BLUE);
+    // The sides split the pixels on the join between them.
+    let join = pixel(&context, 1, 1);
+    assert!(join[0] > 0 && join[2] > 0, "{join:?}");
+    assert_eq!(pixel(&context, 9, 2), CLEAR);
+    assert_eq!(pixel(&context, 5, 5), CLEAR);
+}
+
+#[test]
+fn widgets_draw_borders_through_the_helper() {
+    let root = silica::Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(20.0),
+            height: points(20.0),
+        },
+        ..Default::default()
+    });
+    let gui = root.gui();
+    let child = Container::new(gui.clone());
+    child.set_visual(Some(VisualStyle::default()));
+    child.set_layout(Style {
+        size: Size {
+            width: points(20.0),
+            height: points(20.0),
+        },
+        border: Rect {
+            left: LengthPercentage::Percent(0.1),
+            right: LengthPercentage::Points(1.0),
+            top: LengthPercentage::Points(3.0),
+            bottom: LengthPercentage::Points(1.0),
+        },
+        ..Default::default()
+    });
+    root.add_child(child);
+    gui.emit_layout(None);
+    let mut context = context(20, 20);
+    gui.draw(&mut context, root);
+    let border = Color::to_rgba8(gui.theme().color(ThemeColor::Border));
+    assert_eq!(pixel(&context, 1, 10), border);
+    assert_eq!(pixel(&context, 2, 10), CLEAR);
+    assert_eq!(pixel(&context, 10, 2), border);
+    assert_eq!(pixel(&context, 10, 3), CLEAR);
+    assert_eq!(pixel(&context, 19, 10), border);
+    assert_eq!(pixel(&context, 10, 19), border);
+}
+
+#[test]
+fn dashes_start_at_the_corner() {
+    assert_eq!(border_dashes(12.0, 2.0, BorderStyle::Solid), [(0.0, 12.0)]);
+    assert_eq!(
+        border_dashes(12.0, 1.0, BorderStyle::Dashed),
+        [(0.0, 3.0), (5.0, 8.0), (10.0, 12.0)]
+    );
+    assert_eq!(
+        border_dashes(7.0, 1.0, BorderStyle::Dotted),
+        [(0.0, 1.0), (2.0, 3.0), (4.0, 5.0), (6.0, 7.0)]
+    );
+    assert!(border_dashes(10.0, 0.0, BorderStyle::Dotted).is_empty());
+}
+
+#[test]
+fn dashed_sides_follow_the_shared_pattern() {
+    let mut context = context(12, 12);
+    let red = Some(Color::rgb(1.0, 0.0, 0.0).into());
+    context.draw_border_sides(
+        Size {
+            width: 12.0,
+            height: 12.0,
+        },
+        uniform(1.0),
+        Rect {
+            left: red,
+            right: red,
+            top: red,
+            bottom: red,
+        },
+        CornerRadius::ZERO,
+        BorderStyle::Dashed,
+    );
+    let dashes = border_dashes(12.0, 1.0, BorderStyle::Dashed);
+    // The corner pixels are split between two sides.
+    for i in 1..11 {
+        let along = i as f32 + 0.5;
+        let on = dashes
+            .iter()
+            .any(|&(start, end)| along >= start && along < end);
+        let expected = if on { RED } else { CLEAR };
+        assert_eq!(pixel(&context, i, 0), expected, "top {i}");
+        assert_eq!(pixel(&context, 11, i), expected, "right {i}");
+    }
+}
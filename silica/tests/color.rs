@@ -0,0 +1,86 @@
+use silica::{linear_to_srgb, srgb_to_linear, Color, Theme, ThemeColor};
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn parses_hex_colors() {
+    assert_eq!(Color::parse("#f00"), Ok(Color::rgb(1.0, 0.0, 0.0)));
+    assert_eq!(
+        Color::parse("#00ff0080").unwrap().to_rgba8(),
+        [0, 255, 0, 128]
+    );
+    assert_eq!(Color::parse("#1234").unwrap().to_rgba8(), [17, 34, 51, 68]);
+    assert_eq!(
+        Color::parse(" #336699 ").unwrap(),
+        Color::from_rgb8(0x33, 0x66, 0x99)
+    );
+    for invalid in ["#", "#12", "#12345", "#ggg", "#1234567", "#ééé"] {
+        assert!(Color::parse(invalid).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn parses_css_names() {
+    assert_eq!(Color::parse("red").unwrap().to_rgba8(), [255, 0, 0, 255]);
+    assert_eq!(
+        Color::parse("RebeccaPurple").unwrap().to_rgba8(),
+        [102, 51, 153, 255]
+    );
+    assert_eq!(
+        "lightgoldenrodyellow".parse::<Color>().unwrap().to_rgba8(),
+        [250, 250, 210, 255]
+    );
+    assert_eq!(Color::named("transparent"), Some(Color::TRANSPARENT));
+    assert_eq!(
+        Color::named("aliceblue"),
+        Some(Color::from_rgb8(240, 248, 255))
+    );
+    assert_eq!(
+        Color::named("yellowgreen"),
+        Some(Color::from_rgb8(154, 205, 50))
+    );
+    let error = Color::parse("notacolor").unwrap_err();
+    assert_eq!(error.to_string(), "invalid color: \"notacolor\"");
+}
+
+#[test]
+fn converts_between_srgb_and_linear() {
+    assert_close(srgb_to_linear(0.0), 0.0);
+    assert_close(srgb_to_linear(1.0), 1.0);
+    assert_close(srgb_to_linear(0.5), 0.21404);
+    assert_close(srgb_to_linear(0.04), 0.04 / 12.92);
+    for i in 0..=20 {
+        let v = i as f32 / 20.0;
+        assert_close(linear_to_srgb(srgb_to_linear(v)), v);
+    }
+    let color = Color::rgba(0.2, 0.5, 0.8, 0.25);
+    let [r, g, b, a] = color.to_linear();
+    let back = Color::from_linear(r, g, b, a);
+    for (x, y) in color.to_array().into_iter().zip(back.to_array()) {
+        assert_close(x, y);
+    }
+}
+
+#[test]
+fn lerp_blends_in_linear_light() {
+    let middle = Color::BLACK.lerp(Color::WHITE, 0.5);
+    assert_close(middle.r, linear_to_srgb(0.5));
+    assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.0), Color::BLACK);
+    let end = Color::BLACK.lerp(Color::WHITE, 1.0);
+    assert_close(end.g, 1.0);
+    let alpha = Color::TRANSPARENT.lerp(Color::BLACK, 0.25);
+    assert_close(alpha.a, 0.25);
+}
+
+#[test]
+fn theme_colors_are_looked_up_in_the_theme() {
+    let mut theme = Theme::light();
+    theme.set_color(ThemeColor::Border, Color::rgb(1.0, 0.0, 0.0));
+    assert_eq!(ThemeColor::Border.to_rgba(&theme), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(
+        ThemeColor::Foreground.to_rgba(&Theme::dark()),
+        Theme::dark().color(ThemeColor::Foreground).to_array()
+    );
+}
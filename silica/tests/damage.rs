@@ -0,0 +1,116 @@
+use silica::{
+    model::{BoolModel, IntModel, Model, SelectValue},
+    signal::PointerButton,
+    taffy::prelude::*,
+    widget::{Button, Checkbox, Container},
+    DamageRect, DamageRegion, Gui,
+};
+
+fn setup() -> Container {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size::from_points(400.0, 100.0),
+        ..Default::default()
+    });
+    root
+}
+
+fn settle(root: &Container) {
+    let gui = root.gui();
+    gui.emit_layout(None);
+    gui.take_damage();
+}
+
+fn covers(damage: &DamageRegion, rect: DamageRect) -> bool {
+    damage
+        .rects()
+        .iter()
+        .any(|damaged| damaged.intersection(&rect) == Some(rect))
+}
+
+fn checkbox_rect(checkbox: &Checkbox) -> DamageRect {
+    DamageRect::new(checkbox.window_position(), checkbox.size())
+}
+
+fn button_rect(button: &Button) -> DamageRect {
+    DamageRect::new(button.window_position(), button.size())
+}
+
+#[test]
+fn clicking_a_checkbox_repaints_every_checkbox_on_its_model() {
+    let root = setup();
+    let gui = root.gui();
+    let model = BoolModel::new(false);
+    let checkboxes = [0, 1].map(|_| {
+        let checkbox = Checkbox::new(gui.clone(), model.clone());
+        root.add_child(checkbox.clone());
+        checkbox
+    });
+    settle(&root);
+
+    let first = checkbox_rect(&checkboxes[0]);
+    gui.emit_pointer_motion(first.origin.x + 2.0, first.origin.y + 2.0);
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    gui.take_damage();
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    assert!(model.get());
+    assert!(!gui.needs_layout());
+    let damage = gui.take_damage();
+    for checkbox in &checkboxes {
+        assert!(covers(&damage, checkbox_rect(checkbox)));
+    }
+}
+
+#[test]
+fn setting_a_model_directly_repaints_its_widgets() {
+    let root = setup();
+    let gui = root.gui();
+    let model = BoolModel::new(false);
+    let checkbox = Checkbox::new_rocker(gui.clone(), model.clone());
+    let toggle = Button::with_label_toggle(gui.clone(), "Toggle".to_string(), model.clone());
+    root.add_child(checkbox.clone());
+    root.add_child(toggle.clone());
+    settle(&root);
+
+    model.clone().set(true);
+    let damage = gui.take_damage();
+    assert!(covers(&damage, checkbox_rect(&checkbox)));
+    assert!(covers(&damage, button_rect(&toggle)));
+    assert!(!gui.has_damage());
+}
+
+#[test]
+fn selecting_a_value_repaints_the_whole_group() {
+    let root = setup();
+    let gui = root.gui();
+    let choice = IntModel::new(0);
+    let options = [0, 1, 2].map(|value| {
+        let selected = SelectValue::new(choice.clone(), value);
+        let option = Checkbox::new(gui.clone(), selected);
+        root.add_child(option.clone());
+        option
+    });
+    settle(&root);
+
+    options[2].activate();
+    assert_eq!(choice.get(), 2);
+    let damage = gui.take_damage();
+    for option in &options {
+        assert!(covers(&damage, checkbox_rect(option)));
+    }
+}
+
+#[test]
+fn dropped_widgets_stop_listening() {
+    let root = setup();
+    let gui = root.gui();
+    let model = BoolModel::new(false);
+    let checkbox = Checkbox::new(gui.clone(), model.clone());
+    root.add_child(checkbox.clone());
+    settle(&root);
+    root.remove_children();
+    drop(checkbox);
+    settle(&root);
+    model.clone().set(true);
+    assert!(!gui.has_damage());
+}
@@ -0,0 +1,145 @@
+use std::rc::Rc;
+
+use silica::{
+    taffy::{geometry::Point, prelude::*},
+    Color, CornerRadius, Gradient, GraphicsContext, HeadlessContext, Shadow, Theme,
+};
+
+fn context(width: u32, height: u32) -> HeadlessContext {
+    HeadlessContext::new(width, height, Rc::new(Theme::default()))
+}
+
+fn size(width: f32, height: f32) -> Size<f32> {
+    Size { width, height }
+}
+
+fn red_to_blue() -> Vec<(f32, Color)> {
+    vec![
+        (0.0, Color::rgb(1.0, 0.0, 0.0)),
+        (1.0, Color::rgb(0.0, 0.0, 1.0)),
+    ]
+}
+
+// The red and blue channels of a pixel.
+fn red_blue(context: &HeadlessContext, x: u32, y: u32) -> (u8, u8) {
+    let [r, _, b, _] = context.pixel(x, y).to_rgba8();
+    (r, b)
+}
+
+#[test]
+fn corner_radius_fits_the_box() {
+    let radius = CornerRadius::all(8.0);
+    assert_eq!(radius.clamp(size(40.0, 40.0)), radius);
+    // Two 8px corners share a 10px side, so everything shrinks to 5px.
+    assert_eq!(radius.clamp(size(40.0, 10.0)), CornerRadius::all(5.0));
+
+    let radius = CornerRadius {
+        top_left: 12.0,
+        top_right: 4.0,
+        bottom_right: 0.0,
+        bottom_left: 6.0,
+    };
+    // The top side needs 16px of 8, the tightest ratio.
+    assert_eq!(
+        radius.clamp(size(8.0, 20.0)),
+        CornerRadius {
+            top_left: 6.0,
+            top_right: 2.0,
+            bottom_right: 0.0,
+            bottom_left: 3.0,
+        }
+    );
+    assert_eq!(
+        CornerRadius::all(-3.0).clamp(size(10.0, 10.0)),
+        CornerRadius::ZERO
+    );
+    assert_eq!(radius.clamp(size(0.0, 10.0)), CornerRadius::ZERO);
+}
+
+#[test]
+fn shadow_outset_follows_the_offset() {
+    let shadow = Shadow {
+        offset: Point { x: 3.0, y: -1.0 },
+        blur: 4.0,
+        spread: 1.0,
+        color: Color::BLACK.into(),
+    };
+    assert_eq!(
+        shadow.outset(),
+        Rect {
+            left: 0.0,
+            right: 6.0,
+            top: 4.0,
+            bottom: 2.0,
+        }
+    );
+    // An offset larger than the reach leaves nothing on the near side.
+    let shadow = Shadow {
+        offset: Point { x: -10.0, y: 0.0 },
+        blur: 0.0,
+        spread: -2.0,
+        ..shadow
+    };
+    assert_eq!(
+        shadow.outset(),
+        Rect {
+            left: 10.0,
+            right: 0.0,
+            top: 0.0,
+            bottom: 0.0,
+        }
+    );
+}
+
+#[test]
+fn linear_gradient_runs_along_its_angle() {
+    let mut context = context(10, 10);
+    let gradient = Gradient::linear(90.0, red_to_blue());
+    context.draw_gradient(Point::ZERO, size(10.0, 10.0), CornerRadius::ZERO, &gradient);
+    let (left, right) = (red_blue(&context, 0, 5), red_blue(&context, 9, 5));
+    assert!(left.0 > 230 && left.1 < 25, "{left:?}");
+    assert!(right.1 > 230 && right.0 < 25, "{right:?}");
+    // Pointing right, rows are the same and columns differ.
+    assert_eq!(red_blue(&context, 3, 0), red_blue(&context, 3, 9));
+    let middle = red_blue(&context, 5, 5);
+    assert!(middle.0.abs_diff(middle.1) < 40, "{middle:?}");
+
+    // Pointing up ends at the top; at 45 degrees the ends reach the corners.
+    let mut context = self::context(10, 10);
+    let gradient = Gradient::linear(0.0, red_to_blue());
+    context.draw_gradient(Point::ZERO, size(10.0, 10.0), CornerRadius::ZERO, &gradient);
+    assert!(red_blue(&context, 5, 0).1 > 230);
+    assert!(red_blue(&context, 5, 9).0 > 230);
+    let mut context = self::context(10, 10);
+    let gradient = Gradient::linear(45.0, red_to_blue());
+    context.draw_gradient(Point::ZERO, size(10.0, 10.0), CornerRadius::ZERO, &gradient);
+    assert!(red_blue(&context, 9, 0).1 > 240);
+    assert!(red_blue(&context, 0, 9).0 > 240);
+    assert_eq!(red_blue(&context, 0, 0), red_blue(&context, 9, 9));
+}
+
+#[test]
+fn radial_gradient_reaches_the_corners() {
+    let mut context = context(20, 10);
+    let gradient = Gradient::radial(red_to_blue());
+    context.draw_gradient(Point::ZERO, size(20.0, 10.0), CornerRadius::ZERO, &gradient);
+    assert!(red_blue(&context, 10, 5).0 > 230);
+    for (x, y) in [(0, 0), (19, 0), (0, 9), (19, 9)] {
+        assert!(red_blue(&context, x, y).1 > 230, "({x}, {y})");
+    }
+    assert_eq!(red_blue(&context, 4, 2), red_blue(&context, 15, 7));
+}
+
+#[test]
+fn gradient_is_clipped_to_rounded_corners() {
+    let mut context = context(10, 10);
+    let gradient = Gradient::linear(90.0, red_to_blue());
+    context.draw_gradient(
+        Point::ZERO,
+        size(10.0, 10.0),
+        CornerRadius::all(5.0),
+        &gradient,
+    );
+    assert_eq!(context.pixel(0, 0).to_rgba8(), [0, 0, 0, 0]);
+    assert_eq!(context.pixel(5, 5).to_rgba8()[3], 255);
+}
@@ -0,0 +1,141 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{widget::Container, Event, EventPhase, Gui, Signal, Widget};
+
+#[derive(Clone)]
+struct Ping;
+impl Signal for Ping {}
+
+type Log = Rc<RefCell<Vec<(&'static str, EventPhase)>>>;
+
+// root > middle > leaf, each logging the events it sees in every phase.
+fn setup() -> (Container, [Container; 3], Log) {
+    let root = Gui::new();
+    let middle = Container::new(root.gui());
+    let leaf = Container::new(root.gui());
+    root.add_child(middle.clone());
+    middle.add_child(leaf.clone());
+    let log: Log = Rc::default();
+    for (name, widget) in [("root", &root), ("middle", &middle), ("leaf", &leaf)] {
+        widget.connect_capture({
+            let log = log.clone();
+            move |_, event: Event<Ping>| {
+                if event.phase() == EventPhase::Capture {
+                    log.borrow_mut().push((name, event.phase()));
+                }
+            }
+        });
+        widget.connect_event({
+            let log = log.clone();
+            move |_, event: Event<Ping>| log.borrow_mut().push((name, event.phase()))
+        });
+    }
+    (root.clone(), [root, middle, leaf], log)
+}
+
+fn dispatch(root: &Container, target: &Container) -> bool {
+    let target: Widget = target.clone().into();
+    root.gui().dispatch_event(&target, Ping)
+}
+
+#[test]
+fn capture_then_target_then_bubble() {
+    let (root, [_, middle, leaf], log) = setup();
+    assert!(!dispatch(&root, &leaf));
+    assert_eq!(
+        *log.borrow(),
+        [
+            ("root", EventPhase::Capture),
+            ("middle", EventPhase::Capture),
+            ("leaf", EventPhase::Target),
+            ("middle", EventPhase::Bubble),
+            ("root", EventPhase::Bubble),
+        ]
+    );
+
+    log.borrow_mut().clear();
+    dispatch(&root, &middle);
+    assert_eq!(
+        *log.borrow(),
+        [
+            ("root", EventPhase::Capture),
+            ("middle", EventPhase::Target),
+            ("root", EventPhase::Bubble),
+        ]
+    );
+}
+
+#[test]
+fn capture_and_event_handlers_both_see_the_target_phase() {
+    let (root, [_, _, leaf], _) = setup();
+    let phases = Rc::new(RefCell::new(Vec::new()));
+    leaf.connect_capture({
+        let phases = phases.clone();
+        move |_, event: Event<Ping>| phases.borrow_mut().push(("capture", event.phase()))
+    });
+    leaf.connect_event({
+        let phases = phases.clone();
+        move |target, event: Event<Ping>| {
+            assert_eq!(event.target().node(), target.node());
+            phases.borrow_mut().push(("event", event.phase()))
+        }
+    });
+    dispatch(&root, &leaf);
+    assert_eq!(
+        *phases.borrow(),
+        [
+            ("capture", EventPhase::Target),
+            ("event", EventPhase::Target)
+        ]
+    );
+}
+
+#[test]
+fn stopping_in_capture_skips_the_rest() {
+    let (root, [_, middle, leaf], log) = setup();
+    middle.connect_capture(|_, event: Event<Ping>| {
+        if event.phase() == EventPhase::Capture {
+            event.stop_propagation();
+        }
+    });
+    assert!(dispatch(&root, &leaf));
+    // Handlers already connected on the stopping widget still run.
+    assert_eq!(
+        *log.borrow(),
+        [
+            ("root", EventPhase::Capture),
+            ("middle", EventPhase::Capture),
+        ]
+    );
+}
+
+#[test]
+fn stopping_in_bubble_skips_the_ancestors() {
+    let (root, [_, middle, leaf], log) = setup();
+    middle.connect_event(|_, event: Event<Ping>| event.stop_propagation());
+    let later = Rc::new(RefCell::new(0));
+    middle.connect_event({
+        let later = later.clone();
+        move |_, _: Event<Ping>| *later.borrow_mut() += 1
+    });
+    assert!(dispatch(&root, &leaf));
+    assert_eq!(
+        *log.borrow(),
+        [
+            ("root", EventPhase::Capture),
+            ("middle", EventPhase::Capture),
+            ("leaf", EventPhase::Target),
+            ("middle", EventPhase::Bubble),
+        ]
+    );
+    assert_eq!(*later.borrow(), 1);
+}
+
+#[test]
+fn stopping_at_the_target_skips_bubbling() {
+    let (root, [_, _, leaf], log) = setup();
+    leaf.connect_event(|_, event: Event<Ping>| event.stop_propagation());
+    assert!(dispatch(&root, &leaf));
+    assert_eq!(log.borrow().last(), Some(&("leaf", EventPhase::Target)));
+    assert_eq!(log.borrow().len(), 3);
+}
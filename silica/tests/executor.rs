@@ -0,0 +1,128 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    model::{IntModel, Model},
+    signal::PointerButton,
+    taffy::prelude::*,
+    widget::Button,
+    Gui,
+};
+
+#[test]
+fn task_is_polled_between_events() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let task = gui.spawn_local({
+        let log = log.clone();
+        async move { log.borrow_mut().push("ran") }
+    });
+    assert!(log.borrow().is_empty());
+    assert!(task.is_pending());
+    gui.emit_proxy_events();
+    assert_eq!(*log.borrow(), ["ran"]);
+    assert!(!task.is_pending());
+    assert!(!task.cancel());
+}
+
+#[test]
+fn task_awaits_model_changes() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let model = IntModel::new(0);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    gui.spawn_local({
+        let model = model.clone();
+        let seen = seen.clone();
+        async move {
+            for _ in 0..2 {
+                model.changed().await;
+                seen.borrow_mut().push(model.get());
+            }
+        }
+    });
+    gui.emit_proxy_events();
+    model.clone().set(1);
+    assert!(seen.borrow().is_empty());
+    gui.emit_proxy_events();
+    model.clone().set(2);
+    gui.emit_proxy_events();
+    model.clone().set(3);
+    gui.emit_proxy_events();
+    assert_eq!(*seen.borrow(), [1, 2]);
+}
+
+#[test]
+fn task_awaits_button_activation() {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let gui = root.gui();
+    let button = Button::with_label(gui.clone(), "Button".to_string());
+    root.add_child(button.clone());
+    gui.emit_layout(None);
+    let activated = Rc::new(RefCell::new(false));
+    gui.spawn_local({
+        let activated = activated.clone();
+        async move {
+            button.activated().await;
+            *activated.borrow_mut() = true;
+        }
+    });
+    gui.emit_proxy_events();
+    gui.emit_pointer_motion(10.0, 10.0);
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    gui.emit_proxy_events();
+    assert!(*activated.borrow());
+}
+
+#[test]
+fn cancelled_task_releases_its_captures() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let model = IntModel::new(0);
+    let task = gui.spawn_local({
+        let model = model.clone();
+        let gui = gui.clone();
+        async move {
+            loop {
+                model.changed().await;
+                gui.mark_needs_layout();
+            }
+        }
+    });
+    gui.emit_proxy_events();
+    assert_eq!(Rc::strong_count(&model), 2);
+    assert!(task.cancel());
+    assert!(!task.is_pending());
+    assert_eq!(Rc::strong_count(&model), 1);
+    // A wake after cancelling finds no task.
+    model.clone().set(1);
+    gui.emit_proxy_events();
+}
+
+#[test]
+fn task_can_cancel_itself() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let handle = Rc::new(RefCell::new(None));
+    let model = IntModel::new(0);
+    let task = gui.spawn_local({
+        let handle = handle.clone();
+        let model = model.clone();
+        async move {
+            let handle: Option<silica::TaskHandle> = handle.borrow_mut().take();
+            assert!(handle.unwrap().cancel());
+            model.changed().await;
+        }
+    });
+    *handle.borrow_mut() = Some(task);
+    gui.emit_proxy_events();
+    assert_eq!(Rc::strong_count(&model), 1);
+}
@@ -0,0 +1,133 @@
+use std::rc::Rc;
+
+use silica::{
+    model::{BoolModel, Model},
+    signal::{Key, KeyPress, Modifiers},
+    widget::{Button, Checkbox, Container},
+    Gui, Visibility, WidgetDataUntyped,
+};
+
+fn press(gui: &Rc<Gui>, key: Key, shift: bool) {
+    gui.emit_key(KeyPress {
+        key,
+        modifiers: Modifiers {
+            shift,
+            ..Default::default()
+        },
+        repeat: false,
+        text: None,
+    });
+}
+
+fn setup(count: usize) -> (Container, Vec<Button>) {
+    let root = Gui::new();
+    let buttons = (0..count)
+        .map(|i| {
+            let button = Button::with_label(root.gui(), format!("Button {i}"));
+            root.add_child(button.clone());
+            button
+        })
+        .collect();
+    (root, buttons)
+}
+
+// The position of the focused button in `buttons`.
+fn focus_index(gui: &Gui, buttons: &[Button]) -> Option<usize> {
+    let focus = gui.focused()?;
+    buttons
+        .iter()
+        .position(|button| button.node() == focus.node())
+}
+
+// The buttons that Tab, or Shift+Tab if `backwards`, visits in `steps` presses.
+fn tab_through(root: &Container, buttons: &[Button], steps: usize, backwards: bool) -> Vec<usize> {
+    let gui = root.gui();
+    (0..steps)
+        .map(|_| {
+            press(&gui, Key::Tab, backwards);
+            focus_index(&gui, buttons).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn tab_follows_tree_order_and_wraps() {
+    let (root, buttons) = setup(3);
+    assert_eq!(tab_through(&root, &buttons, 4, false), [0, 1, 2, 0]);
+    assert_eq!(tab_through(&root, &buttons, 3, true), [2, 1, 0]);
+}
+
+#[test]
+fn shift_tab_starts_from_the_end() {
+    let (root, buttons) = setup(3);
+    assert_eq!(tab_through(&root, &buttons, 2, true), [2, 1]);
+}
+
+#[test]
+fn tab_index_comes_before_tree_order() {
+    let (root, buttons) = setup(4);
+    buttons[3].set_tab_index(Some(0));
+    buttons[1].set_tab_index(Some(1));
+    assert_eq!(tab_through(&root, &buttons, 5, false), [3, 1, 0, 2, 3]);
+    assert_eq!(tab_through(&root, &buttons, 4, true), [2, 0, 1, 3]);
+}
+
+#[test]
+fn tab_skips_disabled_and_hidden_widgets() {
+    let (root, buttons) = setup(5);
+    buttons[1].set_enabled(false);
+    buttons[2].set_visibility(Visibility::Hidden);
+    let container = Container::new(root.gui());
+    root.insert_child(3, container.clone());
+    container.add_child(buttons[3].clone());
+    container.set_visible(false);
+    assert_eq!(tab_through(&root, &buttons, 3, false), [0, 4, 0]);
+
+    container.set_visible(true);
+    buttons[1].set_enabled(true);
+    assert_eq!(tab_through(&root, &buttons, 4, false), [1, 3, 4, 0]);
+}
+
+#[test]
+fn space_and_enter_activate_the_focused_button() {
+    let (root, buttons) = setup(2);
+    let gui = root.gui();
+    let model = BoolModel::new(false);
+    let toggle = Button::with_label_toggle(gui.clone(), "Toggle".to_string(), model.clone());
+    root.add_child(toggle.clone());
+
+    press(&gui, Key::Space, false);
+    assert!(!model.get());
+    toggle.grab_focus();
+    press(&gui, Key::Space, false);
+    assert!(model.get());
+    press(&gui, Key::Enter, false);
+    assert!(!model.get());
+    press(&gui, Key::Character('a'), false);
+    assert!(!model.get());
+
+    buttons[0].grab_focus();
+    press(&gui, Key::Enter, false);
+    assert!(!model.get());
+}
+
+#[test]
+fn space_toggles_the_focused_checkbox() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let model = BoolModel::new(false);
+    let checkbox = Checkbox::new(gui.clone(), model.clone());
+    root.add_child(checkbox.clone());
+    press(&gui, Key::Tab, false);
+    assert!(checkbox.has_focus());
+    press(&gui, Key::Space, false);
+    assert!(model.get());
+    press(&gui, Key::Enter, false);
+    assert!(!model.get());
+
+    // A disabled checkbox loses focus and ignores keys.
+    checkbox.set_enabled(false);
+    assert!(gui.focused().is_none());
+    press(&gui, Key::Space, false);
+    assert!(!model.get());
+}
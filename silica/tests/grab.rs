@@ -0,0 +1,110 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    signal::{PointerButton, PointerMotion},
+    taffy::prelude::*,
+    widget::Container,
+    Event, Gui, WidgetDataUntyped,
+};
+
+type Log = Rc<RefCell<Vec<(&'static str, f32)>>>;
+
+// Two 50x50 boxes side by side, logging the x of each motion event they receive.
+fn setup() -> (Container, Container, Container, Log) {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size::from_points(100.0, 50.0),
+        ..Default::default()
+    });
+    let log: Log = Rc::default();
+    let [left, right] = ["left", "right"].map(|name| {
+        let widget = Container::new(root.gui());
+        widget.set_layout(Style {
+            size: Size::from_points(50.0, 50.0),
+            ..Default::default()
+        });
+        widget.connect_event({
+            let log = log.clone();
+            move |_, event: Event<PointerMotion>| log.borrow_mut().push((name, event.signal.x))
+        });
+        root.add_child(widget.clone());
+        widget
+    });
+    root.gui().emit_layout(None);
+    (root, left, right, log)
+}
+
+fn grabbed_by(gui: &Gui, widget: &Container) -> bool {
+    gui.pointer_grab()
+        .is_some_and(|grab| grab.node() == widget.node())
+}
+
+#[test]
+fn press_grabs_until_release() {
+    let (root, left, _, log) = setup();
+    let gui = root.gui();
+    gui.emit_pointer_motion(10.0, 10.0);
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    assert!(grabbed_by(&gui, &left));
+    // Dragging onto the right box still reports to the left one.
+    gui.emit_pointer_motion(70.0, 10.0);
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    assert!(gui.pointer_grab().is_none());
+    gui.emit_pointer_motion(80.0, 10.0);
+    assert_eq!(
+        *log.borrow(),
+        [("left", 10.0), ("left", 70.0), ("right", 80.0)]
+    );
+}
+
+#[test]
+fn explicit_grab_lasts_until_released() {
+    let (root, left, right, log) = setup();
+    let gui = root.gui();
+    right.grab_pointer();
+    assert!(grabbed_by(&gui, &right));
+    gui.emit_pointer_motion(10.0, 10.0);
+    // Pressing and releasing elsewhere keeps the explicit grab.
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    assert!(grabbed_by(&gui, &right));
+    gui.emit_pointer_motion(20.0, 10.0);
+
+    // Only the grabbing widget can release it.
+    left.release_pointer();
+    assert!(grabbed_by(&gui, &right));
+    right.release_pointer();
+    assert!(gui.pointer_grab().is_none());
+    gui.emit_pointer_motion(30.0, 10.0);
+    assert_eq!(
+        *log.borrow(),
+        [("right", 10.0), ("right", 20.0), ("left", 30.0)]
+    );
+}
+
+#[test]
+fn removed_widget_loses_its_grab() {
+    let (root, left, right, log) = setup();
+    let gui = root.gui();
+    right.grab_pointer();
+    right.remove_from_parent();
+    assert!(gui.pointer_grab().is_none());
+    gui.emit_pointer_motion(10.0, 10.0);
+    assert_eq!(*log.borrow(), [("left", 10.0)]);
+
+    root.add_child(right.clone());
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    assert!(grabbed_by(&gui, &left));
+    left.remove_from_parent();
+    assert!(gui.pointer_grab().is_none());
+    gui.emit_pointer_button(PointerButton::Primary(false));
+}
+
+#[test]
+fn hidden_widget_loses_its_grab() {
+    let (root, _, right, _) = setup();
+    let gui = root.gui();
+    right.grab_pointer();
+    right.set_visible(false);
+    assert!(gui.pointer_grab().is_none());
+}
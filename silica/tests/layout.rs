@@ -0,0 +1,104 @@
+use silica::{
+    taffy::prelude::*,
+    widget::{Button, Container, Label},
+    VisualStyle,
+};
+
+fn setup() -> (Container, Button) {
+    let root = silica::Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    root.add_child(button.clone());
+    root.gui().emit_layout(None);
+    root.gui().take_damage();
+    (root, button)
+}
+
+#[test]
+fn layout_runs_once_until_invalidated() {
+    let (root, _) = setup();
+    let gui = root.gui();
+    let count = gui.layout_count();
+    gui.emit_layout(None);
+    gui.emit_layout(None);
+    assert_eq!(gui.layout_count(), count);
+    assert!(!gui.needs_layout());
+}
+
+#[test]
+fn hover_repaints_without_layout() {
+    let (root, button) = setup();
+    let gui = root.gui();
+    let count = gui.layout_count();
+    gui.emit_pointer_motion(10.0, 10.0);
+    assert!(!gui.needs_layout());
+    let damage = gui.take_damage();
+    assert_eq!(damage.bounds().unwrap().size, button.size());
+    gui.emit_layout(None);
+    assert_eq!(gui.layout_count(), count);
+}
+
+#[test]
+fn set_visual_repaints_without_layout() {
+    let (root, button) = setup();
+    let gui = root.gui();
+    button.set_visual(Some(VisualStyle::default()));
+    assert!(gui.has_damage());
+    assert!(!gui.needs_layout());
+}
+
+#[test]
+fn set_layout_needs_layout() {
+    let (root, button) = setup();
+    let gui = root.gui();
+    let count = gui.layout_count();
+    button.set_layout(Style {
+        size: Size {
+            width: points(50.0),
+            height: points(20.0),
+        },
+        ..Default::default()
+    });
+    assert!(gui.needs_layout());
+    gui.emit_layout(None);
+    assert_eq!(gui.layout_count(), count + 1);
+    assert_eq!(button.size().width, 50.0);
+}
+
+#[test]
+fn children_and_text_need_layout() {
+    let (root, button) = setup();
+    let gui = root.gui();
+
+    let label = Label::with_text(gui.clone(), "Label".to_string());
+    root.add_child(label.clone());
+    assert!(gui.needs_layout());
+    gui.emit_layout(None);
+
+    label.set_text("Changed".to_string());
+    assert!(gui.needs_layout());
+    gui.emit_layout(None);
+
+    button.remove_from_parent();
+    assert!(gui.needs_layout());
+}
+
+#[test]
+fn window_size_change_needs_layout() {
+    let (root, _) = setup();
+    let gui = root.gui();
+    let size = Size {
+        width: 300.0,
+        height: 200.0,
+    };
+    let count = gui.layout_count();
+    gui.emit_layout(Some(size));
+    gui.emit_layout(Some(size));
+    assert_eq!(gui.layout_count(), count + 1);
+}
@@ -0,0 +1,132 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    signal::{PointerButton, Scroll},
+    taffy::prelude::*,
+    widget::{Button, Container},
+    Event, Gui,
+};
+
+// A 100x50 window with a 50x50 box on the left, holding a button.
+fn setup() -> (Container, Container, Button) {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size::from_points(100.0, 50.0),
+        ..Default::default()
+    });
+    let panel = Container::new(root.gui());
+    panel.set_layout(Style {
+        size: Size::from_points(50.0, 50.0),
+        ..Default::default()
+    });
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    button.set_layout(Style {
+        size: Size::from_points(50.0, 20.0),
+        ..Default::default()
+    });
+    root.add_child(panel.clone());
+    panel.add_child(button.clone());
+    root.gui().emit_layout(None);
+    (root, panel, button)
+}
+
+fn describe(button: PointerButton) -> String {
+    match button {
+        PointerButton::Primary(pressed) => format!("primary {pressed}"),
+        PointerButton::Secondary(pressed) => format!("secondary {pressed}"),
+        PointerButton::Middle(pressed) => format!("middle {pressed}"),
+    }
+}
+
+#[test]
+fn middle_button_goes_to_the_hovered_widget() {
+    let (root, panel, button) = setup();
+    let gui = root.gui();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    panel.connect_event({
+        let log = log.clone();
+        move |_, event: Event<PointerButton>| log.borrow_mut().push(describe(event.signal))
+    });
+    let activations = Rc::new(RefCell::new(0));
+    button.connect_activate({
+        let activations = activations.clone();
+        move |_, _| *activations.borrow_mut() += 1
+    });
+    gui.emit_pointer_motion(10.0, 30.0);
+    gui.emit_pointer_button(PointerButton::Middle(true));
+    gui.emit_pointer_button(PointerButton::Middle(false));
+    assert_eq!(*log.borrow(), ["middle true", "middle false"]);
+
+    // A middle click on the button reaches it but neither activates nor focuses it.
+    gui.emit_pointer_motion(10.0, 10.0);
+    gui.emit_pointer_button(PointerButton::Middle(true));
+    gui.emit_pointer_button(PointerButton::Middle(false));
+    assert_eq!(log.borrow().len(), 4);
+    assert_eq!(*activations.borrow(), 0);
+    assert!(gui.focused().is_none());
+    assert!(gui.pointer_grab().is_none());
+}
+
+#[test]
+fn scroll_goes_to_the_hovered_widget_and_bubbles() {
+    let (root, panel, _) = setup();
+    let gui = root.gui();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    for (name, widget) in [("root", &root), ("panel", &panel)] {
+        widget.connect_event({
+            let log = log.clone();
+            move |_, event: Event<Scroll>| {
+                log.borrow_mut()
+                    .push((name, event.signal.dx, event.signal.dy))
+            }
+        });
+    }
+    gui.emit_scroll(1.0, 2.0);
+    assert!(log.borrow().is_empty(), "no pointer, no target");
+
+    gui.emit_pointer_motion(10.0, 10.0);
+    gui.emit_scroll(0.0, 3.0);
+    gui.emit_pointer_motion(80.0, 10.0);
+    gui.emit_scroll(-1.0, 0.0);
+    assert_eq!(
+        *log.borrow(),
+        [("panel", 0.0, 3.0), ("root", 0.0, 3.0), ("root", -1.0, 0.0)]
+    );
+}
+
+#[test]
+fn secondary_click_opens_the_nearest_context_menu() {
+    let (root, panel, _) = setup();
+    let gui = root.gui();
+    let menus = Rc::new(RefCell::new(Vec::new()));
+    for (name, widget) in [("root", &root), ("panel", &panel)] {
+        widget.connect_context_menu({
+            let menus = menus.clone();
+            move |_, menu| menus.borrow_mut().push((name, menu.x, menu.y))
+        });
+    }
+    gui.emit_pointer_motion(10.0, 30.0);
+    gui.emit_pointer_button(PointerButton::Secondary(true));
+    gui.emit_pointer_button(PointerButton::Secondary(false));
+    gui.emit_pointer_motion(80.0, 40.0);
+    gui.emit_pointer_button(PointerButton::Secondary(true));
+    assert_eq!(
+        *menus.borrow(),
+        [("panel", 10.0, 30.0), ("root", 80.0, 40.0)]
+    );
+}
+
+#[test]
+fn handled_secondary_press_has_no_context_menu() {
+    let (root, panel, _) = setup();
+    let gui = root.gui();
+    let menus = Rc::new(RefCell::new(0));
+    root.connect_context_menu({
+        let menus = menus.clone();
+        move |_, _| *menus.borrow_mut() += 1
+    });
+    panel.connect_event(|_, event: Event<PointerButton>| event.stop_propagation());
+    gui.emit_pointer_motion(10.0, 30.0);
+    gui.emit_pointer_button(PointerButton::Secondary(true));
+    assert_eq!(*menus.borrow(), 0);
+}
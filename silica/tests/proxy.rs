@@ -0,0 +1,91 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use silica::{Gui, Signal};
+
+#[derive(Clone)]
+struct Loaded(i32);
+impl Signal for Loaded {}
+
+#[test]
+fn posted_closures_run_in_order_on_emit() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    gui.connect_user_event({
+        let log = log.clone();
+        move |_, Loaded(value)| log.borrow_mut().push(value)
+    });
+
+    gui.emit_layout(None);
+
+    let proxy = gui.proxy();
+    thread::spawn(move || {
+        for value in 1..=3 {
+            assert!(proxy.send_user_event(Loaded(value)));
+        }
+        assert!(proxy.post(|gui| gui.mark_needs_layout()));
+    })
+    .join()
+    .unwrap();
+    assert!(log.borrow().is_empty());
+    assert!(!gui.needs_layout());
+
+    gui.emit_proxy_events();
+    assert_eq!(*log.borrow(), [1, 2, 3]);
+    assert!(gui.needs_layout());
+}
+
+#[test]
+fn post_wakes_the_event_loop() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let wakes = Arc::new(AtomicUsize::new(0));
+    gui.set_proxy_waker({
+        let wakes = wakes.clone();
+        move || {
+            wakes.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let proxy = gui.proxy();
+    thread::spawn(move || {
+        proxy.post(|_| {});
+        proxy.post(|_| {});
+    })
+    .join()
+    .unwrap();
+    assert_eq!(wakes.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn closures_posted_while_running_wait_for_next_emit() {
+    let root = Gui::new();
+    let gui = root.gui();
+    let count = Rc::new(RefCell::new(0));
+    let proxy = gui.proxy();
+    gui.proxy().post(move |_| {
+        proxy.post(|_| {});
+    });
+    gui.proxy().post(|gui| {
+        let proxy = gui.proxy();
+        proxy.post(|_| {});
+    });
+    gui.connect_user_event({
+        let count = count.clone();
+        move |_, Loaded(_)| *count.borrow_mut() += 1
+    });
+    gui.proxy().post(|gui| {
+        gui.proxy().send_user_event(Loaded(0));
+    });
+    gui.emit_proxy_events();
+    assert_eq!(*count.borrow(), 0);
+    gui.emit_proxy_events();
+    assert_eq!(*count.borrow(), 1);
+}
@@ -0,0 +1,133 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    model::Model,
+    signal::Scroll,
+    taffy::prelude::*,
+    widget::{Container, ScrollArea},
+    Event, Gui,
+};
+
+// A 100x100 scroll area holding `rows` rows of `row_width`x30, with the pointer over it.
+fn setup(rows: usize, row_width: f32, padding: f32) -> (Container, ScrollArea, Vec<Container>) {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size::from_points(200.0, 200.0),
+        ..Default::default()
+    });
+    let gui = root.gui();
+    let area = ScrollArea::new(gui.clone());
+    let mut layout = area.layout();
+    layout.size = Size::from_points(100.0, 100.0);
+    layout.padding = Rect::points(padding);
+    area.set_layout(layout);
+    root.add_child(area.clone());
+    let rows = (0..rows)
+        .map(|_| {
+            let row = Container::new(gui.clone());
+            row.set_layout(Style {
+                size: Size::from_points(row_width, 30.0),
+                ..Default::default()
+            });
+            area.content().add_child(row.clone());
+            row
+        })
+        .collect();
+    gui.emit_layout(None);
+    gui.emit_pointer_motion(20.0, 20.0);
+    (root, area, rows)
+}
+
+fn offset(area: &ScrollArea) -> (f32, f32) {
+    (area.scroll_x().get(), area.scroll_y().get())
+}
+
+fn bottom(root: &Container, row: &Container) -> f32 {
+    root.gui().window_position(&row.clone().into()).y + 30.0
+}
+
+#[test]
+fn wheel_scrolls_by_lines_and_clamps() {
+    let (root, area, _) = setup(10, 50.0, 0.0);
+    let gui = root.gui();
+    gui.emit_scroll(0.0, 1.0);
+    assert_eq!(offset(&area), (0.0, 40.0));
+    gui.emit_scroll(0.0, 2.5);
+    assert_eq!(offset(&area), (0.0, 140.0));
+    // 300 of content in a 100 high area.
+    gui.emit_scroll(0.0, 10.0);
+    assert_eq!(offset(&area), (0.0, 200.0));
+    // Nothing to scroll sideways.
+    gui.emit_scroll(3.0, 0.0);
+    assert_eq!(offset(&area), (0.0, 200.0));
+    gui.emit_scroll(0.0, -20.0);
+    assert_eq!(offset(&area), (0.0, 0.0));
+}
+
+#[test]
+fn scroll_at_the_limit_bubbles() {
+    let (root, _, _) = setup(10, 50.0, 0.0);
+    let gui = root.gui();
+    let bubbled = Rc::new(RefCell::new(Vec::new()));
+    root.connect_event({
+        let bubbled = bubbled.clone();
+        move |_, event: Event<Scroll>| bubbled.borrow_mut().push(event.signal.dy)
+    });
+    gui.emit_scroll(0.0, 10.0);
+    gui.emit_scroll(0.0, 1.0);
+    gui.emit_scroll(0.0, -1.0);
+    assert_eq!(*bubbled.borrow(), [1.0]);
+}
+
+#[test]
+fn offset_from_the_model_is_clamped() {
+    let (root, area, rows) = setup(10, 50.0, 0.0);
+    area.scroll_y().set(1000.0);
+    root.gui().emit_layout(None);
+    assert_eq!(bottom(&root, &rows[9]), 100.0);
+    area.scroll_y().set(-50.0);
+    assert_eq!(bottom(&root, &rows[0]), 30.0);
+}
+
+#[test]
+fn children_move_with_the_offset() {
+    let (root, area, rows) = setup(10, 50.0, 0.0);
+    let gui = root.gui();
+    let top = |row: &Container| gui.window_position(&row.clone().into()).y;
+    assert_eq!(top(&rows[0]), 0.0);
+    assert_eq!(top(&rows[3]), 90.0);
+    gui.emit_scroll(0.0, 1.0);
+    assert_eq!(offset(&area), (0.0, 40.0));
+    assert_eq!(top(&rows[0]), -40.0);
+    assert_eq!(top(&rows[3]), 50.0);
+
+    // The pointer now hits the row scrolled under it.
+    let hits = Rc::new(RefCell::new(0));
+    rows[1].connect_event({
+        let hits = hits.clone();
+        move |_, _: Event<Scroll>| *hits.borrow_mut() += 1
+    });
+    gui.emit_pointer_motion(20.0, 0.0);
+    gui.emit_scroll(0.0, 1.0);
+    assert_eq!(*hits.borrow(), 1);
+}
+
+#[test]
+fn last_row_stops_at_the_padding() {
+    let (root, area, rows) = setup(10, 50.0, 5.0);
+    root.gui().emit_scroll(0.0, 10.0);
+    assert_eq!(offset(&area).1, 210.0);
+    assert_eq!(bottom(&root, &rows[9]), 95.0);
+}
+
+#[test]
+fn last_row_stops_above_the_horizontal_scrollbar() {
+    // Rows 95 wide fit beside the padding, but not beside the vertical scrollbar, which brings
+    // in a horizontal one.
+    let (root, area, rows) = setup(10, 95.0, 0.0);
+    let gui = root.gui();
+    let bar = gui.theme().scrollbar_width;
+    gui.emit_scroll(10.0, 10.0);
+    assert_eq!(offset(&area), (95.0 - (100.0 - bar), 300.0 - (100.0 - bar)));
+    assert_eq!(bottom(&root, &rows[9]), 100.0 - bar);
+}
@@ -0,0 +1,218 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    model::{IntModel, Model},
+    signal::{self, PointerButton},
+    taffy::prelude::*,
+    widget::{Button, Container},
+    Signal, Signals,
+};
+
+#[derive(Clone)]
+struct Ping(u32);
+impl Signal for Ping {}
+
+fn click_setup() -> (Container, Button) {
+    let root = silica::Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    root.add_child(button.clone());
+    root.gui().emit_layout(None);
+    (root, button)
+}
+
+fn click(root: &Container) {
+    let gui = root.gui();
+    gui.emit_pointer_motion(10.0, 10.0);
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    gui.emit_pointer_button(PointerButton::Primary(false));
+}
+
+#[test]
+fn handlers_run_in_connection_order() {
+    let signals = Signals::<()>::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    for name in ["a", "b", "c"] {
+        let log = log.clone();
+        signals.connect(move |(), Ping(n)| log.borrow_mut().push(format!("{name} {n}")));
+    }
+    signals.emit((), Ping(0));
+    signals.emit((), Ping(1));
+    assert_eq!(*log.borrow(), ["a 0", "b 0", "c 0", "a 1", "b 1", "c 1"]);
+}
+
+#[test]
+fn dropping_a_scoped_connection_disconnects() {
+    let signals = Signals::<()>::new();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let scoped = signals
+        .connect({
+            let calls = calls.clone();
+            move |(), Ping(n)| calls.borrow_mut().push(("scoped", n))
+        })
+        .scoped();
+    let released = signals
+        .connect({
+            let calls = calls.clone();
+            move |(), Ping(n)| calls.borrow_mut().push(("released", n))
+        })
+        .scoped()
+        .release();
+    signals.emit((), Ping(0));
+    drop(scoped);
+    signals.emit((), Ping(1));
+    assert_eq!(
+        *calls.borrow(),
+        [("scoped", 0), ("released", 0), ("released", 1)]
+    );
+    assert!(released.disconnect());
+}
+
+#[test]
+fn nested_emit_is_queued() {
+    let signals = Rc::new(Signals::<()>::new());
+    let log = Rc::new(RefCell::new(Vec::new()));
+    signals.connect({
+        let signals = Rc::downgrade(&signals);
+        let log = log.clone();
+        move |(), Ping(n)| {
+            log.borrow_mut().push(format!("start {n}"));
+            if n < 2 {
+                signals.upgrade().unwrap().emit((), Ping(n + 1));
+            }
+            log.borrow_mut().push(format!("end {n}"));
+        }
+    });
+    signals.emit((), Ping(0));
+    assert_eq!(
+        *log.borrow(),
+        ["start 0", "end 0", "start 1", "end 1", "start 2", "end 2"]
+    );
+}
+
+#[test]
+fn connect_during_emit_applies_to_next_emit() {
+    let signals = Rc::new(Signals::<()>::new());
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    signals.connect({
+        let signals = Rc::downgrade(&signals);
+        let calls = calls.clone();
+        move |(), Ping(n)| {
+            calls.borrow_mut().push(("outer", n));
+            let calls = calls.clone();
+            signals
+                .upgrade()
+                .unwrap()
+                .connect(move |(), Ping(n)| calls.borrow_mut().push(("inner", n)));
+        }
+    });
+    signals.emit((), Ping(0));
+    assert_eq!(*calls.borrow(), [("outer", 0)]);
+    signals.emit((), Ping(1));
+    assert_eq!(*calls.borrow(), [("outer", 0), ("outer", 1), ("inner", 1)]);
+}
+
+#[test]
+fn disconnect_during_emit_skips_handler() {
+    let signals = Signals::<()>::new();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let second = Rc::new(RefCell::new(None::<silica::Connection>));
+    signals.connect({
+        let second = second.clone();
+        move |(), Ping(_)| {
+            if let Some(connection) = second.borrow().as_ref() {
+                connection.disconnect();
+            }
+        }
+    });
+    *second.borrow_mut() = Some(signals.connect({
+        let calls = calls.clone();
+        move |(), Ping(n)| calls.borrow_mut().push(n)
+    }));
+    signals.emit((), Ping(0));
+    assert!(calls.borrow().is_empty());
+}
+
+#[test]
+fn cell_model_set_in_change_handler() {
+    let model = IntModel::new(0);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    model.connect_change(|model, signal::Change| {
+        if model.get() > 10 {
+            model.set(10);
+        }
+    });
+    model.connect_change({
+        let seen = seen.clone();
+        move |model, signal::Change| seen.borrow_mut().push(model.get())
+    });
+    model.clone().set(15);
+    assert_eq!(model.get(), 10);
+    assert_eq!(*seen.borrow(), [0, 10, 10]);
+}
+
+#[test]
+fn button_connect_in_activate_handler() {
+    let (root, button) = click_setup();
+    let count = Rc::new(RefCell::new(0));
+    button.connect_activate({
+        let count = count.clone();
+        move |button, signal::Activate| {
+            let count = count.clone();
+            button.connect_activate(move |_, signal::Activate| *count.borrow_mut() += 1);
+        }
+    });
+    click(&root);
+    assert_eq!(*count.borrow(), 0);
+    click(&root);
+    assert_eq!(*count.borrow(), 1);
+}
+
+#[test]
+fn gui_emit_in_activate_handler() {
+    let (root, button) = click_setup();
+    let count = Rc::new(RefCell::new(0));
+    button.connect_activate({
+        let count = count.clone();
+        move |button, signal::Activate| {
+            *count.borrow_mut() += 1;
+            if *count.borrow() == 1 {
+                let gui = button.gui();
+                gui.emit_layout(None);
+                gui.emit_pointer_button(PointerButton::Primary(true));
+                gui.emit_pointer_button(PointerButton::Primary(false));
+            }
+        }
+    });
+    click(&root);
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn panicking_handler_does_not_block_later_emits() {
+    let signals = Rc::new(Signals::<()>::new());
+    let log = Rc::new(RefCell::new(Vec::new()));
+    signals.connect({
+        let signals = Rc::downgrade(&signals);
+        let log = log.clone();
+        move |(), Ping(n)| {
+            log.borrow_mut().push(n);
+            if n == 0 {
+                signals.upgrade().unwrap().emit((), Ping(1));
+                panic!("handler failed");
+            }
+        }
+    });
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        signals.emit((), Ping(0));
+    }));
+    assert!(result.is_err());
+    signals.emit((), Ping(2));
+    assert_eq!(*log.borrow(), [0, 2]);
+}
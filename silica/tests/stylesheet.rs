@@ -0,0 +1,249 @@
+use silica::{
+    taffy::prelude::*,
+    widget::{Button, Container, Label},
+    Color, StyleColor, StyleSheet, StyleSheetError, ThemeColor,
+};
+
+fn setup(sheet: &str) -> Container {
+    let root = silica::Gui::new();
+    root.gui()
+        .set_style_sheet(Some(StyleSheet::parse(sheet).unwrap()));
+    root
+}
+
+fn parse_error(sheet: &str) -> (usize, String) {
+    match StyleSheet::parse(sheet) {
+        Err(StyleSheetError::Parse { line, message }) => (line, message),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+fn red() -> Option<StyleColor> {
+    Some(Color::parse("red").unwrap().into())
+}
+
+#[test]
+fn parse_errors_report_lines() {
+    assert_eq!(
+        parse_error("Button { color: red; }\n\nLabel { colour: red; }"),
+        (3, "unknown property \"colour\"".to_string())
+    );
+    assert_eq!(
+        parse_error("Button {\n  color: red;\n  padding 4px;\n}"),
+        (3, "expected ':' in \"padding 4px\"".to_string())
+    );
+    assert_eq!(
+        parse_error("/* a\ncomment */ Button"),
+        (2, "expected '{' after selector".to_string())
+    );
+    assert_eq!(parse_error("\nButton { color: red;").0, 2);
+    assert_eq!(
+        parse_error("Button:wiggle { color: red; }").1,
+        "unknown pseudo-class \"wiggle\""
+    );
+    assert_eq!(
+        parse_error("Button:hover Label { color: red; }").1,
+        "pseudo-classes must come last, in \"Button:hover Label\""
+    );
+    assert_eq!(parse_error("Button { padding: 1 2 3 4 5; }").0, 1);
+    let error = StyleSheet::parse("\nButton { width: wide; }").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 2: expected a number, found \"wide\""
+    );
+}
+
+#[test]
+fn non_ascii_selectors_are_errors() {
+    assert_eq!(parse_error("é { color: red; }").0, 1);
+    assert_eq!(parse_error("Button.é { color: red; }").0, 1);
+    assert_eq!(parse_error("\n#ü { color: red; }").0, 2);
+}
+
+#[test]
+fn state_rules_only_set_colors() {
+    for declaration in ["min-height: 4px", "transition: 0s", "border-radius: 2px"] {
+        let (line, message) = parse_error(&format!("\nButton:hover {{ {declaration}; }}"));
+        assert_eq!(line, 2);
+        assert!(message.contains("can't be set for a state"), "{message}");
+    }
+    StyleSheet::parse("Button:hover { background: red; border-color: blue; color: none; }")
+        .unwrap();
+}
+
+#[test]
+fn descendant_selectors_match_ancestors() {
+    let root =
+        setup("#sidebar Label { min-height: 20px; } Container Container Label { flex-grow: 2; }");
+    let gui = root.gui();
+    let sidebar = Container::new(gui.clone());
+    sidebar.set_id(Some("sidebar".to_string()));
+    let inner = Container::new(gui.clone());
+    let nested = Label::with_text(gui.clone(), "Nested".to_string());
+    let outside = Label::with_text(gui.clone(), "Outside".to_string());
+    root.add_child(sidebar.clone());
+    sidebar.add_child(inner.clone());
+    inner.add_child(nested.clone());
+    root.add_child(outside.clone());
+
+    assert_eq!(nested.computed_layout().min_size.height, points(20.0));
+    assert_eq!(nested.computed_layout().flex_grow, 2.0);
+    assert_eq!(outside.computed_layout().min_size.height, Dimension::Auto);
+    assert_eq!(outside.computed_layout().flex_grow, 0.0);
+}
+
+#[test]
+fn specificity_then_order_decides() {
+    let root = setup(
+        "
+        Button.big { min-height: 40px; }
+        .big { min-height: 30px; }
+        Button { min-height: 10px; flex-grow: 1; }
+        Button { flex-grow: 2; }
+        #ok { flex-grow: 5; }
+        ",
+    );
+    let gui = root.gui();
+    let button = Button::with_label(gui.clone(), "Button".to_string());
+    root.add_child(button.clone());
+    assert_eq!(button.computed_layout().min_size.height, points(10.0));
+    assert_eq!(button.computed_layout().flex_grow, 2.0);
+
+    button.add_class("big");
+    assert_eq!(button.computed_layout().min_size.height, points(40.0));
+    button.set_id(Some("ok".to_string()));
+    assert_eq!(button.computed_layout().flex_grow, 5.0);
+
+    let rules = gui.matched_rules(&button.widget());
+    let selectors: Vec<&str> = rules.iter().map(|rule| rule.selector()).collect();
+    assert_eq!(selectors, ["Button", "Button", ".big", "Button.big", "#ok"]);
+    assert_eq!(rules[0].line(), 4);
+    assert_eq!(rules[3].specificity(), (0, 1, 1));
+    assert_eq!(rules[2].to_string(), ".big { min-height: 30px; } (line 3)");
+}
+
+#[test]
+fn shorthands_expand() {
+    let root = setup(
+        "
+        .a { padding: 1px 2px 3px 4px; margin: 5px auto; }
+        .b { padding: 1px 2%; flex: 2 3 10px; gap: 4px 8px; }
+        .c { flex: 4; gap: 6px; size: 10px 50%; }
+        ",
+    );
+    let gui = root.gui();
+    let [a, b, c] = ["a", "b", "c"].map(|class| {
+        let widget = Container::new(gui.clone());
+        widget.add_class(class);
+        root.add_child(widget.clone());
+        widget.computed_layout()
+    });
+    assert_eq!(
+        a.padding,
+        Rect {
+            top: LengthPercentage::Points(1.0),
+            right: LengthPercentage::Points(2.0),
+            bottom: LengthPercentage::Points(3.0),
+            left: LengthPercentage::Points(4.0),
+        }
+    );
+    assert_eq!(a.margin.left, LengthPercentageAuto::Auto);
+    assert_eq!(a.margin.top, LengthPercentageAuto::Points(5.0));
+    assert_eq!(b.padding.left, LengthPercentage::Percent(0.02));
+    assert_eq!(b.padding.bottom, LengthPercentage::Points(1.0));
+    assert_eq!((b.flex_grow, b.flex_shrink), (2.0, 3.0));
+    assert_eq!(b.flex_basis, points(10.0));
+    assert_eq!(
+        b.gap,
+        Size {
+            width: LengthPercentage::Points(8.0),
+            height: LengthPercentage::Points(4.0),
+        }
+    );
+    assert_eq!((c.flex_grow, c.flex_shrink), (4.0, 1.0));
+    assert_eq!(c.gap.width, LengthPercentage::Points(6.0));
+    assert_eq!(
+        c.size,
+        Size {
+            width: points(10.0),
+            height: percent(0.5),
+        }
+    );
+}
+
+#[test]
+fn state_rules_set_overrides() {
+    let root = setup(
+        "
+        Button { background: theme(button-press); }
+        Button:hover { background: red; }
+        Button:disabled { color: none; }
+        ",
+    );
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    root.add_child(button.clone());
+    let visual = button.widget().visual().clone().unwrap();
+    assert_eq!(visual.background, Some(ThemeColor::ButtonPress.into()));
+    assert_eq!(visual.hover.background, red());
+    assert_eq!(visual.disabled.foreground, None);
+    assert_eq!(visual.transition, silica::VisualStyle::BUTTON.transition);
+}
+
+#[test]
+fn rules_are_undone_when_they_stop_matching() {
+    let root = setup(
+        "
+        .big { min-height: 40px; background: red; }
+        #sidebar Container { flex-grow: 3; }
+        ",
+    );
+    let gui = root.gui();
+    let sidebar = Container::new(gui.clone());
+    sidebar.set_id(Some("sidebar".to_string()));
+    root.add_child(sidebar.clone());
+    let widget = Container::new(gui.clone());
+    widget.set_layout(Style {
+        min_size: Size {
+            width: points(5.0),
+            height: points(5.0),
+        },
+        ..Default::default()
+    });
+    sidebar.add_child(widget.clone());
+    widget.add_class("big");
+    assert_eq!(widget.computed_layout().min_size.height, points(40.0));
+    assert_eq!(widget.computed_layout().flex_grow, 3.0);
+    assert_eq!(widget.widget().visual().as_ref().unwrap().background, red());
+
+    widget.remove_class("big");
+    assert_eq!(widget.computed_layout().min_size.height, points(5.0));
+    assert!(widget.widget().visual().is_none());
+
+    // Moving out of the sidebar drops the descendant rule.
+    root.add_child(widget.clone());
+    assert_eq!(widget.computed_layout().flex_grow, 0.0);
+    sidebar.add_child(widget.clone());
+    assert_eq!(widget.computed_layout().flex_grow, 3.0);
+    widget.remove_from_parent();
+    assert_eq!(widget.computed_layout().flex_grow, 0.0);
+
+    // The widget's own layout stays separate from what the sheet sets.
+    sidebar.add_child(widget.clone());
+    widget.add_class("big");
+    assert_eq!(widget.layout().min_size.height, points(5.0));
+    let mut layout = widget.layout();
+    layout.min_size.width = points(7.0);
+    widget.set_layout(layout);
+    assert_eq!(
+        widget.computed_layout().min_size,
+        Size::from_points(7.0, 40.0)
+    );
+
+    gui.set_style_sheet(None);
+    assert_eq!(
+        widget.computed_layout().min_size,
+        Size::from_points(7.0, 5.0)
+    );
+    assert_eq!(widget.computed_layout().flex_grow, 0.0);
+    assert!(widget.widget().visual().is_none());
+}
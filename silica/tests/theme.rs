@@ -0,0 +1,126 @@
+use silica::{
+    model::BoolModel,
+    taffy::prelude::*,
+    widget::{Button, Checkbox},
+    Color, Theme, ThemeColor, ThemeError,
+};
+
+#[test]
+fn empty_file_is_the_light_theme() {
+    assert_eq!(Theme::from_toml("").unwrap(), Theme::light());
+}
+
+#[test]
+fn base_selects_the_preset() {
+    for (base, preset) in [
+        ("light", Theme::light()),
+        ("dark", Theme::dark()),
+        ("high-contrast", Theme::high_contrast()),
+    ] {
+        let theme = Theme::from_toml(&format!("base = {base:?}")).unwrap();
+        assert_eq!(theme, preset, "{base}");
+    }
+}
+
+#[test]
+fn keys_override_the_base() {
+    let theme = Theme::from_toml(
+        r##"
+        base = "dark"
+        border_width = 2.5
+        button_min_size = [96.0, 28.0]
+
+        [colors]
+        button_over = "#3d5a80"
+        foreground = "white"
+        "##,
+    )
+    .unwrap();
+    let dark = Theme::dark();
+    assert_eq!(theme.border_width, 2.5);
+    assert_eq!(theme.padding, dark.padding);
+    assert_eq!(
+        theme.button_min_size,
+        Size {
+            width: 96.0,
+            height: 28.0
+        }
+    );
+    assert_eq!(
+        theme.color(ThemeColor::ButtonOver),
+        Color::from_rgb8(0x3d, 0x5a, 0x80)
+    );
+    assert_eq!(theme.color(ThemeColor::Foreground), Color::WHITE);
+    assert_eq!(
+        theme.color(ThemeColor::Border),
+        dark.color(ThemeColor::Border)
+    );
+}
+
+#[test]
+fn unknown_base_is_rejected() {
+    let error = Theme::from_toml(r#"base = "sepia""#).unwrap_err();
+    assert!(matches!(error, ThemeError::UnknownBase(ref name) if name == "sepia"));
+    assert_eq!(error.to_string(), "unknown base theme: \"sepia\"");
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let error = Theme::from_toml("border_widht = 2.0").unwrap_err();
+    assert!(matches!(error, ThemeError::Toml(_)), "{error:?}");
+    let message = error.to_string();
+    assert!(message.starts_with("invalid theme: "), "{message}");
+    assert!(message.contains("border_widht"), "{message}");
+
+    let error = Theme::from_toml("[colors]\nhighlight = \"red\"").unwrap_err();
+    assert!(matches!(error, ThemeError::UnknownColor(ref name) if name == "highlight"));
+    assert_eq!(error.to_string(), "unknown theme color: \"highlight\"");
+}
+
+#[test]
+fn bad_values_are_rejected() {
+    let error = Theme::from_toml("[colors]\nborder = \"#12\"").unwrap_err();
+    assert!(matches!(error, ThemeError::Color(_)), "{error:?}");
+    assert_eq!(error.to_string(), "invalid color: \"#12\"");
+    assert!(std::error::Error::source(&error).is_some());
+
+    let error = Theme::from_toml("padding = \"wide\"").unwrap_err();
+    assert!(matches!(error, ThemeError::Toml(_)), "{error:?}");
+}
+
+#[test]
+fn load_reports_missing_files() {
+    let error = Theme::load("/nonexistent/theme.toml").unwrap_err();
+    assert!(matches!(error, ThemeError::Io(_)), "{error:?}");
+    assert!(error.to_string().starts_with("failed to read theme: "));
+}
+
+#[test]
+fn theme_metrics_are_widget_defaults() {
+    let root = silica::Gui::new();
+    let gui = root.gui();
+    let button = Button::with_label(gui.clone(), "Button".to_string());
+    let custom = Button::with_label(gui.clone(), "Custom".to_string());
+    let checkbox = Checkbox::new(gui.clone(), BoolModel::new(false));
+    let mut layout = custom.layout();
+    layout.padding = Rect::points(10.0);
+    custom.set_layout(layout);
+
+    let mut theme = Theme::light();
+    theme.padding = 6.0;
+    theme.button_min_size = Size {
+        width: 100.0,
+        height: 30.0,
+    };
+    theme.checkbox_min_size = Size {
+        width: 20.0,
+        height: 20.0,
+    };
+    gui.set_theme(theme);
+
+    assert_eq!(button.layout().padding, Rect::points(6.0));
+    assert_eq!(button.layout().min_size, Size::from_points(100.0, 30.0));
+    assert_eq!(custom.layout().padding, Rect::points(10.0));
+    assert_eq!(custom.layout().min_size, Size::from_points(100.0, 30.0));
+    assert_eq!(checkbox.layout().min_size, Size::from_points(20.0, 20.0));
+}
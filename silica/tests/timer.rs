@@ -0,0 +1,226 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use silica::{taffy::prelude::*, widget::Label, Gui, MockClock};
+
+fn setup() -> (Rc<Gui>, Rc<MockClock>) {
+    let root = Gui::new();
+    let gui = root.gui();
+    let clock = MockClock::new();
+    gui.set_clock(clock.clone());
+    (gui, clock)
+}
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn timeout_fires_once_after_duration() {
+    let (gui, clock) = setup();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let handle = gui.add_timeout(ms(100), {
+        let log = log.clone();
+        move |_| log.borrow_mut().push("timeout")
+    });
+    assert_eq!(gui.next_deadline(), Some(gui.now() + ms(100)));
+
+    clock.advance(ms(99));
+    gui.emit_timers();
+    assert!(log.borrow().is_empty());
+
+    clock.advance(ms(1));
+    gui.emit_timers();
+    clock.advance(ms(100));
+    gui.emit_timers();
+    assert_eq!(*log.borrow(), ["timeout"]);
+    assert!(!handle.is_pending());
+    assert_eq!(gui.next_deadline(), None);
+}
+
+#[test]
+fn cancelled_timeout_does_not_fire() {
+    let (gui, clock) = setup();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let handle = gui.add_timeout(ms(10), {
+        let log = log.clone();
+        move |_| log.borrow_mut().push("timeout")
+    });
+    assert!(handle.cancel());
+    assert!(!handle.cancel());
+    clock.advance(ms(10));
+    gui.emit_timers();
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn timers_fire_in_deadline_order() {
+    let (gui, clock) = setup();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    for (name, delay) in [("c", 30), ("a", 10), ("b", 20)] {
+        let log = log.clone();
+        gui.add_timeout(ms(delay), move |_| log.borrow_mut().push(name));
+    }
+    clock.advance(ms(30));
+    gui.emit_timers();
+    assert_eq!(*log.borrow(), ["a", "b", "c"]);
+}
+
+#[test]
+fn callback_can_cancel_later_timer() {
+    let (gui, clock) = setup();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let second = gui.add_timeout(ms(20), {
+        let log = log.clone();
+        move |_| log.borrow_mut().push("second")
+    });
+    gui.add_timeout(ms(10), {
+        let log = log.clone();
+        move |_| {
+            log.borrow_mut().push("first");
+            second.cancel();
+        }
+    });
+    clock.advance(ms(20));
+    gui.emit_timers();
+    assert_eq!(*log.borrow(), ["first"]);
+}
+
+#[test]
+fn timeout_added_in_callback_waits_for_next_emit() {
+    let (gui, clock) = setup();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    gui.add_timeout(ms(10), {
+        let log = log.clone();
+        move |gui| {
+            log.borrow_mut().push("outer");
+            let log = log.clone();
+            gui.add_timeout(Duration::ZERO, move |_| log.borrow_mut().push("inner"));
+        }
+    });
+    clock.advance(ms(10));
+    gui.emit_timers();
+    assert_eq!(*log.borrow(), ["outer"]);
+    gui.emit_timers();
+    assert_eq!(*log.borrow(), ["outer", "inner"]);
+}
+
+#[test]
+fn interval_repeats_until_cancelled() {
+    let (gui, clock) = setup();
+    let count = Rc::new(RefCell::new(0));
+    let handle = gui.add_interval(ms(10), {
+        let count = count.clone();
+        move |_| *count.borrow_mut() += 1
+    });
+    for _ in 0..3 {
+        clock.advance(ms(10));
+        gui.emit_timers();
+    }
+    assert_eq!(*count.borrow(), 3);
+    assert_eq!(gui.next_deadline(), Some(gui.now() + ms(10)));
+
+    // Missed periods are skipped.
+    clock.advance(ms(35));
+    gui.emit_timers();
+    assert_eq!(*count.borrow(), 4);
+    assert_eq!(gui.next_deadline(), Some(gui.now() + ms(10)));
+
+    assert!(handle.cancel());
+    clock.advance(ms(10));
+    gui.emit_timers();
+    assert_eq!(*count.borrow(), 4);
+    assert_eq!(gui.next_deadline(), None);
+}
+
+#[test]
+fn interval_can_cancel_itself() {
+    let (gui, clock) = setup();
+    let count = Rc::new(RefCell::new(0));
+    let handle: Rc<RefCell<Option<silica::TimerHandle>>> = Rc::default();
+    *handle.borrow_mut() = Some(gui.add_interval(ms(10), {
+        let count = count.clone();
+        let handle = handle.clone();
+        move |_| {
+            *count.borrow_mut() += 1;
+            if *count.borrow() == 2 {
+                handle.borrow().as_ref().unwrap().cancel();
+            }
+        }
+    }));
+    for _ in 0..5 {
+        clock.advance(ms(10));
+        gui.emit_timers();
+    }
+    assert_eq!(*count.borrow(), 2);
+}
+
+fn tooltip_setup() -> (Rc<Gui>, Rc<MockClock>) {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let label = Label::with_text(root.gui(), "Label".to_string());
+    label.set_layout(Style {
+        size: Size {
+            width: points(50.0),
+            height: points(20.0),
+        },
+        ..Default::default()
+    });
+    label.set_tooltip(Some("Tooltip".to_string()));
+    root.add_child(label);
+    let gui = root.gui();
+    let clock = MockClock::new();
+    gui.set_clock(clock.clone());
+    gui.set_tooltip_delay(ms(500));
+    gui.set_tooltip_timeout(ms(1000));
+    gui.emit_layout(None);
+    (gui, clock)
+}
+
+#[test]
+fn tooltip_appears_after_delay() {
+    let (gui, clock) = tooltip_setup();
+
+    gui.emit_pointer_motion(10.0, 10.0);
+    clock.advance(ms(300));
+    gui.emit_timers();
+    // Moving restarts the delay.
+    gui.emit_pointer_motion(11.0, 10.0);
+    clock.advance(ms(300));
+    gui.emit_timers();
+    assert_eq!(gui.shown_tooltip(), None);
+    clock.advance(ms(200));
+    gui.emit_timers();
+    assert_eq!(gui.shown_tooltip().as_deref(), Some("Tooltip"));
+
+    clock.advance(ms(1000));
+    gui.emit_timers();
+    assert_eq!(gui.shown_tooltip(), None);
+
+    // Leaving the widget and coming back shows it again.
+    gui.emit_pointer_motion(100.0, 50.0);
+    gui.emit_pointer_motion(10.0, 10.0);
+    clock.advance(ms(500));
+    gui.emit_timers();
+    assert_eq!(gui.shown_tooltip().as_deref(), Some("Tooltip"));
+    gui.emit_pointer_motion(100.0, 50.0);
+    assert_eq!(gui.shown_tooltip(), None);
+}
+
+#[test]
+fn tooltip_motion_replaces_pending_timer() {
+    let (gui, clock) = tooltip_setup();
+    for x in 0..10 {
+        gui.emit_pointer_motion(10.0 + x as f32, 10.0);
+        clock.advance(ms(100));
+    }
+    // Only the delay started by the last motion is pending.
+    assert_eq!(gui.next_deadline(), Some(gui.now() + ms(400)));
+    gui.emit_timers();
+    assert_eq!(gui.shown_tooltip(), None);
+}
@@ -0,0 +1,227 @@
+use std::{cell::Cell, rc::Rc};
+
+use silica::{
+    signal::{Key, KeyPress, Modifiers, PointerButton},
+    taffy::prelude::*,
+    widget::{Button, Container},
+    Gui, WidgetDataUntyped,
+};
+
+fn child(root: &Container, width: f32) -> Container {
+    let child = Container::new(root.gui());
+    child.set_layout(Style {
+        size: Size {
+            width: points(width),
+            height: points(10.0),
+        },
+        ..Default::default()
+    });
+    child
+}
+
+fn setup() -> (Container, Vec<Container>) {
+    let root = silica::Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(100.0),
+            height: points(10.0),
+        },
+        ..Default::default()
+    });
+    let children = (1..=3)
+        .map(|i| {
+            let child = child(&root, i as f32);
+            root.add_child(child.clone());
+            child
+        })
+        .collect();
+    (root, children)
+}
+
+// The widths of the children of `root` in layout order. Each child's width is its number, so this
+// checks that the taffy tree matches `children`.
+fn laid_out(root: &Container) -> Vec<f32> {
+    root.gui().emit_layout(None);
+    let mut children: Vec<(f32, f32)> = root
+        .children()
+        .iter()
+        .map(|child| {
+            let position = root.gui().window_position(child);
+            let width = WidgetDataUntyped::layout(&**child).size.width;
+            let Dimension::Points(width) = width else {
+                panic!("child without a fixed width");
+            };
+            (position.x, width)
+        })
+        .collect();
+    let order: Vec<f32> = children.iter().map(|&(_, width)| width).collect();
+    children.sort_by(|a, b| a.0.total_cmp(&b.0));
+    assert_eq!(
+        order,
+        children.iter().map(|&(_, width)| width).collect::<Vec<_>>(),
+        "children and taffy tree are out of sync"
+    );
+    order
+}
+
+#[test]
+fn insert_move_and_replace_keep_tree_in_sync() {
+    let (root, children) = setup();
+    assert_eq!(laid_out(&root), [1.0, 2.0, 3.0]);
+
+    assert!(root.insert_child(0, children[2].clone()));
+    assert_eq!(laid_out(&root), [3.0, 1.0, 2.0]);
+    assert_eq!(children[2].index_in_parent(), Some(0));
+
+    assert!(root.move_child(0, 2));
+    assert_eq!(laid_out(&root), [1.0, 2.0, 3.0]);
+
+    let other = child(&root, 4.0);
+    let old = root.replace_child(children[1].node(), other.clone());
+    assert!(old.is_some());
+    assert!(children[1].parent().is_none());
+    assert_eq!(laid_out(&root), [1.0, 4.0, 3.0]);
+
+    assert!(children[0].remove_from_parent());
+    assert_eq!(laid_out(&root), [4.0, 3.0]);
+}
+
+#[test]
+fn adding_a_child_of_another_parent_detaches_it() {
+    let (root, children) = setup();
+    let other = child(&root, 4.0);
+    root.add_child(other.clone());
+    assert!(other.add_child(children[0].clone()));
+    assert_eq!(laid_out(&root), [2.0, 3.0, 4.0]);
+    assert_eq!(other.children().len(), 1);
+    assert!(children[0]
+        .ancestors()
+        .any(|ancestor| ancestor.node() == root.node()));
+}
+
+#[test]
+fn cycles_are_rejected() {
+    let (root, children) = setup();
+    let inner = Container::new(root.gui());
+    children[0].add_child(inner.clone());
+
+    assert!(!root.add_child(root.clone()));
+    assert!(!inner.add_child(root.clone()));
+    assert!(!inner.add_child(children[0].clone()));
+    assert!(!inner.insert_child(0, root.clone()));
+    assert!(children[0]
+        .replace_child(inner.node(), root.clone())
+        .is_none());
+    assert_eq!(inner.parent().unwrap().node(), children[0].node());
+    assert!(root.parent().is_none());
+    assert_eq!(children[0].parent().unwrap().node(), root.node());
+    assert_eq!(laid_out(&root), [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn out_of_range_indices_are_rejected() {
+    let (root, children) = setup();
+    let other = child(&root, 4.0);
+    assert!(!root.insert_child(4, other.clone()));
+    assert!(other.parent().is_none());
+    // Re-inserting an existing child leaves one fewer position.
+    assert!(!root.insert_child(3, children[0].clone()));
+    assert!(root.insert_child(2, children[0].clone()));
+    assert!(!root.move_child(3, 0));
+    assert!(!root.move_child(0, 3));
+    assert_eq!(laid_out(&root), [2.0, 3.0, 1.0]);
+}
+
+fn press_space(gui: &Rc<Gui>) {
+    gui.emit_key(KeyPress {
+        key: Key::Space,
+        modifiers: Modifiers::default(),
+        repeat: false,
+        text: Some(" ".to_string()),
+    });
+}
+
+// A button filling the first child of `setup`, counting its activations.
+fn counting_button(root: &Container, parent: &Container) -> (Button, Rc<Cell<u32>>) {
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    button.set_layout(Style {
+        min_size: Size::from_points(1.0, 10.0),
+        ..Default::default()
+    });
+    let count = Rc::new(Cell::new(0));
+    button.connect_activate({
+        let count = count.clone();
+        move |_, _| count.set(count.get() + 1)
+    });
+    parent.add_child(button.clone());
+    root.gui().emit_layout(None);
+    (button, count)
+}
+
+#[test]
+fn removing_a_focused_widget_drops_focus() {
+    let (root, children) = setup();
+    let gui = root.gui();
+    let (button, count) = counting_button(&root, &children[0]);
+    let references = Rc::strong_count(&button.widget());
+    assert!(button.grab_focus());
+    press_space(&gui);
+    assert_eq!(count.get(), 1);
+
+    assert!(children[0].remove_from_parent());
+    assert!(gui.focused().is_none());
+    press_space(&gui);
+    assert_eq!(count.get(), 1);
+    assert_eq!(Rc::strong_count(&button.widget()), references);
+}
+
+#[test]
+fn every_removal_drops_focus() {
+    let (root, children) = setup();
+    let gui = root.gui();
+    let (button, _) = counting_button(&root, &children[0]);
+
+    button.grab_focus();
+    children[0].remove_child(button.node());
+    assert!(gui.focused().is_none());
+
+    children[0].add_child(button.clone());
+    button.grab_focus();
+    children[0].remove_children();
+    assert!(gui.focused().is_none());
+
+    children[0].add_child(button.clone());
+    button.grab_focus();
+    root.replace_child(children[0].node(), child(&root, 4.0));
+    assert!(gui.focused().is_none());
+}
+
+#[test]
+fn moving_a_focused_widget_keeps_focus() {
+    let (root, children) = setup();
+    let gui = root.gui();
+    let (button, count) = counting_button(&root, &children[0]);
+    button.grab_focus();
+    children[1].add_child(button.clone());
+    assert!(button.has_focus());
+    press_space(&gui);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn removing_a_pressed_widget_drops_highlight_and_grab() {
+    let (root, children) = setup();
+    let gui = root.gui();
+    let (button, count) = counting_button(&root, &children[0]);
+    let references = Rc::strong_count(&button.widget());
+    gui.emit_pointer_motion(0.5, 5.0);
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    assert!(gui.pointer_grab().is_some());
+
+    button.remove_from_parent();
+    assert!(gui.pointer_grab().is_none());
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    assert_eq!(count.get(), 0);
+    // Only the old parent's reference is gone.
+    assert_eq!(Rc::strong_count(&button.widget()), references - 1);
+}
@@ -0,0 +1,141 @@
+use std::{cell::RefCell, rc::Rc};
+
+use silica::{
+    signal::PointerMotion,
+    taffy::prelude::*,
+    widget::{Button, Container},
+    Color, Event, EventPhase, Gui, HeadlessContext, Visibility,
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+// A 100x20 row of two red 20x20 boxes, the first holding a button.
+fn setup() -> (Container, [Container; 2], Button) {
+    let root = Gui::new();
+    root.set_layout(Style {
+        size: Size::from_points(100.0, 20.0),
+        ..Default::default()
+    });
+    let boxes = [0, 1].map(|_| {
+        let widget = Container::new(root.gui());
+        widget.set_layout(Style {
+            size: Size::from_points(20.0, 20.0),
+            ..Default::default()
+        });
+        widget.set_visual(Some(silica::VisualStyle {
+            background: Some(Color::rgb(1.0, 0.0, 0.0).into()),
+            ..Default::default()
+        }));
+        root.add_child(widget.clone());
+        widget
+    });
+    let button = Button::with_label(root.gui(), "Button".to_string());
+    button.set_layout(Style {
+        size: Size::from_points(10.0, 10.0),
+        ..Default::default()
+    });
+    boxes[0].add_child(button.clone());
+    root.gui().emit_layout(None);
+    (root, boxes, button)
+}
+
+fn draw(root: &Container) -> HeadlessContext {
+    let gui = root.gui();
+    gui.emit_layout(None);
+    let mut context = HeadlessContext::new(100, 20, gui.theme());
+    gui.draw(&mut context, root.clone());
+    context
+}
+
+fn pixel(context: &HeadlessContext, x: u32) -> [u8; 4] {
+    context.pixel(x, 15).to_rgba8()
+}
+
+#[test]
+fn collapsed_widget_takes_no_space() {
+    let (root, boxes, _) = setup();
+    boxes[0].set_visible(false);
+    root.gui().emit_layout(None);
+    assert_eq!(boxes[1].window_position().x, 0.0);
+
+    boxes[0].set_visible(true);
+    root.gui().emit_layout(None);
+    assert_eq!(boxes[1].window_position().x, 20.0);
+}
+
+#[test]
+fn hidden_widget_keeps_its_space() {
+    let (root, boxes, _) = setup();
+    boxes[0].set_visibility(Visibility::Hidden);
+    assert!(!root.gui().needs_layout());
+    assert_eq!(boxes[1].window_position().x, 20.0);
+}
+
+#[test]
+fn hidden_widgets_are_not_drawn() {
+    let (root, boxes, _) = setup();
+    let context = draw(&root);
+    assert_eq!(pixel(&context, 5), RED);
+    assert_eq!(pixel(&context, 25), RED);
+
+    boxes[0].set_visibility(Visibility::Hidden);
+    let context = draw(&root);
+    assert_eq!(pixel(&context, 5), CLEAR);
+    assert_eq!(pixel(&context, 25), RED);
+
+    boxes[0].set_visible(false);
+    let context = draw(&root);
+    assert_eq!(pixel(&context, 5), RED);
+    assert_eq!(pixel(&context, 25), CLEAR);
+
+    boxes[0].set_visible(true);
+    let context = draw(&root);
+    assert_eq!(pixel(&context, 5), RED);
+    assert_eq!(pixel(&context, 25), RED);
+}
+
+#[test]
+fn hidden_widgets_are_not_hit() {
+    let (root, boxes, _) = setup();
+    let gui = root.gui();
+    let hits = Rc::new(RefCell::new(Vec::new()));
+    for (name, widget) in [("root", &root), ("box", &boxes[0])] {
+        widget.connect_event({
+            let hits = hits.clone();
+            move |_, event: Event<PointerMotion>| {
+                if event.phase() == EventPhase::Target {
+                    hits.borrow_mut().push(name);
+                }
+            }
+        });
+    }
+    gui.emit_pointer_motion(5.0, 15.0);
+    boxes[0].set_visibility(Visibility::Hidden);
+    gui.emit_pointer_motion(6.0, 15.0);
+    boxes[0].set_visibility(Visibility::Visible);
+    gui.emit_pointer_motion(7.0, 15.0);
+    assert_eq!(*hits.borrow(), ["box", "root", "box"]);
+}
+
+#[test]
+fn hiding_drops_focus_and_tab_skips_hidden_widgets() {
+    let (root, boxes, button) = setup();
+    let gui = root.gui();
+    assert!(button.grab_focus());
+    boxes[0].set_visibility(Visibility::Hidden);
+    assert!(gui.focused().is_none());
+    assert!(!button.grab_focus());
+    gui.focus_next();
+    assert!(gui.focused().is_none());
+
+    boxes[0].set_visibility(Visibility::Visible);
+    gui.focus_next();
+    assert!(button.has_focus());
+
+    // Collapsing works the same way.
+    boxes[0].set_visible(false);
+    assert!(gui.focused().is_none());
+    boxes[0].set_visible(true);
+    assert!(button.grab_focus());
+}
@@ -0,0 +1,117 @@
+use silica::{
+    signal::PointerButton, taffy::prelude::*, widget::Button, Color, StyleColor, ThemeColor,
+    VisualColors, VisualState, VisualStyle,
+};
+
+fn color(r: f32, g: f32, b: f32) -> Option<StyleColor> {
+    Some(Color::rgb(r, g, b).into())
+}
+
+fn background(colors: Option<VisualColors>) -> Option<StyleColor> {
+    colors.unwrap().background
+}
+
+#[test]
+fn custom_visual_survives_hover_and_press() {
+    let root = silica::Gui::new();
+    root.set_layout(Style {
+        size: Size {
+            width: points(200.0),
+            height: points(100.0),
+        },
+        ..Default::default()
+    });
+    let gui = root.gui();
+    let button = Button::with_label(gui.clone(), "Button".to_string());
+    root.add_child(button.clone());
+    gui.emit_layout(None);
+    let visual = VisualStyle {
+        background: color(1.0, 0.0, 0.0),
+        hover: VisualColors {
+            background: color(0.0, 0.0, 1.0),
+            ..VisualColors::NONE
+        },
+        pressed: VisualColors::NONE,
+        transition: Default::default(),
+        ..VisualStyle::BUTTON
+    };
+    button.set_visual(Some(visual.clone()));
+
+    gui.emit_pointer_motion(10.0, 10.0);
+    assert_eq!(background(button.visual_colors()), color(0.0, 0.0, 1.0));
+    gui.emit_pointer_button(PointerButton::Primary(true));
+    // No pressed override, so the hover color stays.
+    assert_eq!(background(button.visual_colors()), color(0.0, 0.0, 1.0));
+    gui.emit_pointer_button(PointerButton::Primary(false));
+    gui.emit_pointer_motion(150.0, 90.0);
+    assert_eq!(background(button.visual_colors()), color(1.0, 0.0, 0.0));
+    assert_eq!(button.widget().visual().as_ref(), Some(&visual));
+
+    button.set_enabled(false);
+    assert_eq!(
+        background(button.visual_colors()),
+        Some(ThemeColor::ButtonDisable.into())
+    );
+}
+
+#[test]
+fn later_state_overrides_take_precedence() {
+    let visual = VisualStyle {
+        background: color(0.0, 0.0, 0.0),
+        border: color(0.0, 0.0, 0.0),
+        foreground: color(0.0, 0.0, 0.0),
+        checked: VisualColors {
+            background: color(0.1, 0.0, 0.0),
+            border: color(0.1, 0.0, 0.0),
+            foreground: color(0.1, 0.0, 0.0),
+        },
+        focused: VisualColors {
+            background: color(0.2, 0.0, 0.0),
+            border: color(0.2, 0.0, 0.0),
+            ..VisualColors::NONE
+        },
+        hover: VisualColors {
+            background: color(0.3, 0.0, 0.0),
+            ..VisualColors::NONE
+        },
+        pressed: VisualColors::NONE,
+        disabled: VisualColors {
+            foreground: color(0.5, 0.0, 0.0),
+            ..VisualColors::NONE
+        },
+        ..VisualStyle::default()
+    };
+    let all = VisualState {
+        hovered: true,
+        pressed: true,
+        focused: true,
+        disabled: true,
+        checked: true,
+    };
+    assert_eq!(
+        visual.colors(all),
+        VisualColors {
+            background: color(0.3, 0.0, 0.0),
+            border: color(0.2, 0.0, 0.0),
+            foreground: color(0.5, 0.0, 0.0),
+        }
+    );
+    assert_eq!(visual.colors(VisualState::default()), visual.normal());
+    let checked = VisualState {
+        checked: true,
+        ..VisualState::default()
+    };
+    assert_eq!(visual.colors(checked), visual.checked);
+    let focused_and_checked = VisualState {
+        focused: true,
+        ..checked
+    };
+    assert_eq!(
+        visual.colors(focused_and_checked).foreground,
+        color(0.1, 0.0, 0.0)
+    );
+    assert_eq!(
+        visual.colors(focused_and_checked).border,
+        color(0.2, 0.0, 0.0)
+    );
+}